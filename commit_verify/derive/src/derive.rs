@@ -19,10 +19,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use proc_macro2::TokenStream as TokenStream2;
-use syn::Result;
+use amplify_syn::{DataInner, Field, Fields};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::{Error, Index, Result, Type};
 
-use crate::params::{CommitDerive, StrategyAttr};
+use crate::params::{CommitDerive, ConcealDerive, ConcealFieldAttr, FieldAttr, StrategyAttr};
 
 impl CommitDerive {
     pub fn derive_encode(&self) -> Result<TokenStream2> {
@@ -47,6 +49,12 @@ impl CommitDerive {
                 use amplify::Wrapper;
                 engine.commit_to_merkle(self.as_inner().merklize());
             },
+            StrategyAttr::Fields => self.derive_fields()?,
+        };
+
+        let layout = match self.conf.strategy {
+            StrategyAttr::Fields => self.derive_field_layout()?,
+            _ => quote! {},
         };
 
         Ok(quote! {
@@ -58,6 +66,180 @@ impl CommitDerive {
                     #inner
                 }
             }
+
+            #layout
+        })
+    }
+
+    fn struct_fields(&self) -> Result<Vec<(TokenStream2, &Field)>> {
+        Ok(match &self.data.inner {
+            DataInner::Struct(Fields::Named(fields)) => fields
+                .iter()
+                .map(|named| (named.name.to_token_stream(), &named.field))
+                .collect(),
+            DataInner::Struct(Fields::Unnamed(fields)) => fields
+                .iter()
+                .enumerate()
+                .map(|(index, field)| (Index::from(index).to_token_stream(), field))
+                .collect(),
+            _ => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "commitment encoding strategy `fields` can be used only with structures \
+                     having fields",
+                ));
+            }
+        })
+    }
+
+    fn derive_fields(&self) -> Result<TokenStream2> {
+        let fields = self.struct_fields()?;
+        let mut steps = Vec::with_capacity(fields.len());
+        for (name, field) in fields {
+            let attr = FieldAttr::try_from(field.attr.clone())?;
+            steps.push(match attr {
+                FieldAttr::Skip => continue,
+                FieldAttr::Serialized => {
+                    check_serialized(field)?;
+                    quote! { engine.commit_to_serialized(&self.#name); }
+                }
+                FieldAttr::Conceal => quote! { engine.commit_to_concealed(&self.#name); },
+                FieldAttr::Option => quote! { engine.commit_to_option(&self.#name); },
+                FieldAttr::List => quote! { engine.commit_to_list(&self.#name); },
+                FieldAttr::Set => quote! { engine.commit_to_set(&self.#name); },
+                FieldAttr::Map => quote! { engine.commit_to_map(&self.#name); },
+                FieldAttr::Hash => quote! {
+                    engine.commit_to_hash(::core::clone::Clone::clone(&self.#name));
+                },
+                FieldAttr::Merkle => quote! { engine.commit_to_merkle(&self.#name); },
+            });
+        }
+
+        Ok(quote! { #( #steps )* })
+    }
+
+    fn derive_field_layout(&self) -> Result<TokenStream2> {
+        let (impl_generics, ty_generics, where_clause) = self.data.generics.split_for_impl();
+        let trait_crate = &self.conf.commit_crate;
+        let ident_name = &self.data.name;
+
+        let fields = self.struct_fields()?;
+        let mut descriptors = Vec::with_capacity(fields.len());
+        for (name, field) in fields {
+            let attr = FieldAttr::try_from(field.attr.clone())?;
+            let name = name.to_string();
+            let kind = match attr {
+                FieldAttr::Serialized => quote! { Serialized },
+                FieldAttr::Skip => quote! { Skipped },
+                FieldAttr::Conceal => quote! { Concealed },
+                FieldAttr::Option => quote! { Option },
+                FieldAttr::List => quote! { List },
+                FieldAttr::Set => quote! { Set },
+                FieldAttr::Map => quote! { Map },
+                FieldAttr::Hash => quote! { Hashed },
+                FieldAttr::Merkle => quote! { Merklized },
+            };
+            descriptors.push(quote! {
+                #trait_crate::CommitField {
+                    name: #name,
+                    kind: #trait_crate::CommitFieldKind::#kind,
+                }
+            });
+        }
+
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #trait_crate::CommitFields for #ident_name #ty_generics #where_clause {
+                const COMMIT_FIELDS: &'static [#trait_crate::CommitField] = &[ #( #descriptors ),* ];
+            }
+        })
+    }
+}
+
+/// Rejects fields of the types which can't be committed to with
+/// `CommitEngine::commit_to_serialized`, since they either have no strict
+/// type name or require a dedicated commitment procedure.
+fn check_serialized(field: &Field) -> Result<()> {
+    let Type::Path(path) = &field.ty else {
+        return Ok(());
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return Ok(());
+    };
+    let msg = match segment.ident.to_string().as_str() {
+        "Option" => "optional fields must be committed with `#[commit_encode(option)]`",
+        "Confined" | "NonEmptyVec" | "NonEmptyOrdSet" | "NonEmptyOrdMap" | "TinyVec" |
+        "SmallVec" | "MediumVec" | "TinyBlob" | "SmallBlob" | "MediumBlob" | "TinyOrdSet" |
+        "SmallOrdSet" | "MediumOrdSet" | "TinyOrdMap" | "SmallOrdMap" | "MediumOrdMap" => {
+            "collection fields must be committed with `#[commit_encode(list)]`, \
+             `#[commit_encode(set)]` or `#[commit_encode(map)]`"
+        }
+        "Vec" | "BTreeSet" | "BTreeMap" | "HashSet" | "HashMap" | "String" => {
+            "unconfined collections can't be committed to; use confined collection types"
+        }
+        "MerkleHash" | "StrictHash" => {
+            "fields with precomputed hashes can't be committed to; commit to the source data with \
+             `#[commit_encode(merkle)]` or `#[commit_encode(hash)]` instead"
+        }
+        _ => return Ok(()),
+    };
+    Err(Error::new_spanned(&field.ty, msg))
+}
+
+impl ConcealDerive {
+    pub fn derive_conceal(&self) -> Result<TokenStream2> {
+        let (impl_generics, ty_generics, where_clause) = self.data.generics.split_for_impl();
+        let trait_crate = &self.conf.commit_crate;
+        let ident_name = &self.data.name;
+        let concealed = match &self.conf.concealed {
+            Some(path) => path.to_token_stream(),
+            None => quote! { Self },
+        };
+
+        let field_value = |name: TokenStream2, field: &Field| -> Result<TokenStream2> {
+            let attr = ConcealFieldAttr::try_from(field.attr.clone())?;
+            Ok(if attr.conceal {
+                quote! { #trait_crate::Conceal::conceal(&self.#name) }
+            } else {
+                quote! { ::core::clone::Clone::clone(&self.#name) }
+            })
+        };
+
+        let inner = match &self.data.inner {
+            DataInner::Struct(Fields::Named(fields)) => {
+                let mut values = Vec::with_capacity(fields.len());
+                for named in fields.iter() {
+                    let name = &named.name;
+                    let value = field_value(name.to_token_stream(), &named.field)?;
+                    values.push(quote! { #name: #value });
+                }
+                quote! { #concealed { #( #values ),* } }
+            }
+            DataInner::Struct(Fields::Unnamed(fields)) => {
+                let mut values = Vec::with_capacity(fields.len());
+                for (index, field) in fields.iter().enumerate() {
+                    values.push(field_value(Index::from(index).to_token_stream(), field)?);
+                }
+                quote! { #concealed ( #( #values ),* ) }
+            }
+            DataInner::Struct(Fields::Unit) => quote! { #concealed },
+            _ => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "conceal procedure can be derived only for structures",
+                ));
+            }
+        };
+
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #trait_crate::Conceal for #ident_name #ty_generics #where_clause {
+                type Concealed = #concealed;
+
+                fn conceal(&self) -> Self::Concealed {
+                    #inner
+                }
+            }
         })
     }
 }
@@ -44,6 +44,51 @@
 //!
 //! Derivation macros accept `#[commit_encoding()]` attribute with the following
 //! arguments:
+//! - `id = Type` (required): the type of the commitment id, implementing
+//!   `CommitmentId`. The commitment is a hash tagged with `CommitmentId::TAG`
+//!   of the id type, such that the tag is defined once per id type and can't be
+//!   mistyped at the individual implementations; types committing under ids
+//!   with different tags never produce the same commitment;
+//! - `strategy = ...` (required): one of `strict`, `conceal`, `transparent`,
+//!   `merklize` or `fields`, defining how the data are encoded for the
+//!   commitment;
+//! - `crate = path` (optional): path to the `commit_verify` crate.
+//!
+//! ## Attribute arguments at field level
+//!
+//! With `strategy = fields` the structure is committed field by field in their
+//! declaration order using `CommitEngine::commit_to_serialized`. Individual
+//! fields may be annotated with one of:
+//! - `#[commit_encode(skip)]`, excluding the field from the commitment (for
+//!   instance ephemeral caches or signatures);
+//! - `#[commit_encode(conceal)]`, committing to the concealed field value with
+//!   `CommitEngine::commit_to_concealed`;
+//! - `#[commit_encode(option)]` for `Option` fields, using
+//!   `CommitEngine::commit_to_option`;
+//! - `#[commit_encode(list)]`, `#[commit_encode(set)]` and
+//!   `#[commit_encode(map)]` for confined collections, using
+//!   `CommitEngine::commit_to_list`, `commit_to_set` and `commit_to_map`;
+//! - `#[commit_encode(hash)]`, committing to the `StrictHash` commitment id of
+//!   the field value with `CommitEngine::commit_to_hash`;
+//! - `#[commit_encode(merkle)]`, committing to the merkle root of the field
+//!   value with `CommitEngine::commit_to_merkle`.
+//!
+//! Fields of `Option`, collection and precomputed hash types without one of
+//! these attributes are rejected at compile time, since they can't be
+//! committed with `CommitEngine::commit_to_serialized`.
+//!
+//! For such structures the macro also implements `CommitFields` trait, listing
+//! names of all the fields and their treatment by the commitment procedure.
+//!
+//! # Conceal derivation
+//!
+//! `#[derive(`[`Conceal`]`)]` implements conceal procedure for structures,
+//! where the fields marked with `#[conceal(conceal)]` are replaced with their
+//! concealed values, and the rest of the fields are cloned. By default, the
+//! concealed value has the same type as the original one, requiring the
+//! concealed fields to conceal into their own type; a different type having
+//! the same fields may be provided with `#[conceal(concealed = Type)]`
+//! attribute at the type declaration level.
 
 #[macro_use]
 extern crate quote;
@@ -61,7 +106,7 @@ mod derive;
 use proc_macro::TokenStream;
 use syn::DeriveInput;
 
-use crate::params::CommitDerive;
+use crate::params::{CommitDerive, ConcealDerive};
 
 /// Derives [`CommitEncode`] implementation for the type.
 #[proc_macro_derive(CommitEncode, attributes(commit_encode))]
@@ -72,3 +117,14 @@ pub fn derive_commit_encode(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+/// Derives [`Conceal`] implementation for a structure, concealing the fields
+/// marked with `#[conceal(conceal)]` attribute and cloning the rest of them.
+#[proc_macro_derive(Conceal, attributes(conceal))]
+pub fn derive_conceal(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    ConcealDerive::try_from(derive_input)
+        .and_then(|engine| engine.derive_conceal())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -19,7 +19,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use amplify_syn::{ArgValueReq, AttrReq, DataType, ParametrizedAttr, TypeClass};
+use amplify_syn::{ArgValueReq, AttrReq, DataType, ListReq, ParametrizedAttr, TypeClass};
 use proc_macro2::Span;
 use quote::ToTokens;
 use syn::{DeriveInput, Error, Path, Result};
@@ -32,6 +32,16 @@ const ATTR_STRATEGY_STRICT: &str = "strict";
 const ATTR_STRATEGY_CONCEAL: &str = "conceal";
 const ATTR_STRATEGY_TRANSPARENT: &str = "transparent";
 const ATTR_STRATEGY_MERKLIZE: &str = "merklize";
+const ATTR_STRATEGY_FIELDS: &str = "fields";
+const ATTR_SKIP: &str = "skip";
+const ATTR_CONCEAL: &str = "conceal";
+const ATTR_CONCEALED: &str = "concealed";
+const ATTR_OPTION: &str = "option";
+const ATTR_LIST: &str = "list";
+const ATTR_SET: &str = "set";
+const ATTR_MAP: &str = "map";
+const ATTR_HASH: &str = "hash";
+const ATTR_MERKLE: &str = "merkle";
 
 pub struct ContainerAttr {
     pub commit_crate: Path,
@@ -45,6 +55,21 @@ pub enum StrategyAttr {
     ConcealStrict,
     Transparent,
     Merklize,
+    Fields,
+}
+
+/// Procedure used to commit to a field with `strategy = fields`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FieldAttr {
+    Serialized,
+    Skip,
+    Conceal,
+    Option,
+    List,
+    Set,
+    Map,
+    Hash,
+    Merkle,
 }
 
 impl TryFrom<&Path> for StrategyAttr {
@@ -56,12 +81,14 @@ impl TryFrom<&Path> for StrategyAttr {
             ATTR_STRATEGY_CONCEAL => Ok(StrategyAttr::ConcealStrict),
             ATTR_STRATEGY_TRANSPARENT => Ok(StrategyAttr::Transparent),
             ATTR_STRATEGY_MERKLIZE => Ok(StrategyAttr::Merklize),
+            ATTR_STRATEGY_FIELDS => Ok(StrategyAttr::Fields),
             unknown => Err(Error::new(
                 Span::call_site(),
                 format!(
                     "invalid commitment encoding value for `strategy` attribute `{unknown}`; only \
                      `{ATTR_STRATEGY_TRANSPARENT}`, `{ATTR_STRATEGY_STRICT}`, \
-                     `{ATTR_STRATEGY_CONCEAL}`, or `{ATTR_STRATEGY_MERKLIZE}`  are allowed"
+                     `{ATTR_STRATEGY_CONCEAL}`, `{ATTR_STRATEGY_MERKLIZE}`, or \
+                     `{ATTR_STRATEGY_FIELDS}` are allowed"
                 ),
             )),
         }
@@ -93,6 +120,111 @@ impl TryFrom<ParametrizedAttr> for ContainerAttr {
     }
 }
 
+impl TryFrom<ParametrizedAttr> for FieldAttr {
+    type Error = Error;
+
+    fn try_from(mut params: ParametrizedAttr) -> Result<Self> {
+        let mut req = AttrReq::with(none!());
+        req.path_req = ListReq::Many {
+            whitelist: Some(vec![
+                path!(skip),
+                path!(conceal),
+                path!(option),
+                path!(list),
+                path!(set),
+                path!(map),
+                path!(hash),
+                path!(merkle),
+            ]),
+            required: false,
+            max_no: Some(1),
+        };
+        params.check(req)?;
+
+        Ok(
+            match params
+                .paths
+                .first()
+                .map(|path| path.to_token_stream().to_string())
+                .as_deref()
+            {
+                None => FieldAttr::Serialized,
+                Some(ATTR_SKIP) => FieldAttr::Skip,
+                Some(ATTR_CONCEAL) => FieldAttr::Conceal,
+                Some(ATTR_OPTION) => FieldAttr::Option,
+                Some(ATTR_LIST) => FieldAttr::List,
+                Some(ATTR_SET) => FieldAttr::Set,
+                Some(ATTR_MAP) => FieldAttr::Map,
+                Some(ATTR_HASH) => FieldAttr::Hash,
+                Some(ATTR_MERKLE) => FieldAttr::Merkle,
+                Some(_) => unreachable!("attribute paths are whitelisted"),
+            },
+        )
+    }
+}
+
+pub struct ConcealContainerAttr {
+    pub commit_crate: Path,
+    pub concealed: Option<Path>,
+}
+
+pub struct ConcealFieldAttr {
+    pub conceal: bool,
+}
+
+impl TryFrom<ParametrizedAttr> for ConcealContainerAttr {
+    type Error = Error;
+
+    fn try_from(mut params: ParametrizedAttr) -> Result<Self> {
+        let req = AttrReq::with(map![
+            ATTR_CRATE => ArgValueReq::optional(TypeClass::Path),
+            ATTR_CONCEALED => ArgValueReq::optional(TypeClass::Path),
+        ]);
+        params.check(req)?;
+
+        Ok(ConcealContainerAttr {
+            commit_crate: params
+                .arg_value(ATTR_CRATE)
+                .unwrap_or_else(|_| path!(commit_verify)),
+            concealed: params.arg_value(ATTR_CONCEALED).ok(),
+        })
+    }
+}
+
+impl TryFrom<ParametrizedAttr> for ConcealFieldAttr {
+    type Error = Error;
+
+    fn try_from(mut params: ParametrizedAttr) -> Result<Self> {
+        let mut req = AttrReq::with(none!());
+        req.path_req = ListReq::Many {
+            whitelist: Some(vec![path!(conceal)]),
+            required: false,
+            max_no: Some(1),
+        };
+        params.check(req)?;
+
+        Ok(ConcealFieldAttr {
+            conceal: params.has_verbatim(ATTR_CONCEAL),
+        })
+    }
+}
+
+pub struct ConcealDerive {
+    pub data: DataType,
+    pub conf: ConcealContainerAttr,
+}
+
+impl TryFrom<DeriveInput> for ConcealDerive {
+    type Error = Error;
+
+    fn try_from(input: DeriveInput) -> Result<Self> {
+        let params = ParametrizedAttr::with(ATTR_CONCEAL, &input.attrs)?;
+        let conf = ConcealContainerAttr::try_from(params)?;
+        let data = DataType::with(input, ident!(conceal))?;
+        Ok(Self { data, conf })
+    }
+}
+
 pub struct CommitDerive {
     pub data: DataType,
     pub conf: ContainerAttr,
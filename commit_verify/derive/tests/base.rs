@@ -30,8 +30,13 @@ mod common;
 
 use std::fmt::Display;
 
+use amplify::confinement::{SmallBlob, TinyOrdMap, TinyOrdSet, TinyVec};
 use amplify::{Bytes32, Wrapper};
-use commit_verify::{CommitEncode, CommitId, CommitmentId, Conceal, DigestExt, Sha256};
+use commit_verify::mpc::Leaf;
+use commit_verify::{
+    CommitEncode, CommitEngine, CommitField, CommitFieldKind, CommitFields, CommitId, CommitStep,
+    CommitmentId, Conceal, DigestExt, Sha256, StrictHash,
+};
 use strict_encoding::{StrictDecode, StrictDumb, StrictEncode};
 
 const TEST_LIB: &str = "TestLib";
@@ -54,11 +59,58 @@ impl From<Sha256> for DumbId {
     fn from(value: Sha256) -> Self { value.finish().into() }
 }
 
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From, Display)]
+#[display(inner)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = TEST_LIB)]
+struct OtherId(
+    #[from]
+    #[from([u8; 32])]
+    Bytes32,
+);
+
+impl CommitmentId for OtherId {
+    const TAG: &'static str = "urn:test:other#2024-10-14";
+}
+
+impl From<Sha256> for OtherId {
+    fn from(value: Sha256) -> Self { value.finish().into() }
+}
+
 fn verify_commit<T: CommitId>(value: T, expect: &'static str)
 where T::CommitmentId: Display {
     assert_eq!(&value.commit_id().to_string(), expect, "invalid commitment");
 }
 
+#[test]
+fn tag_separation() -> common::Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = strict, id = DumbId)]
+    struct First(u64);
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = strict, id = OtherId)]
+    struct Second(u64);
+
+    for value in [0u64, 1, 0xbeefcafebaddafec, u64::MAX] {
+        let first = First(value).commit_id();
+        let second = Second(value).commit_id();
+        assert_ne!(first.0, second.0);
+
+        let mut hasher = Sha256::from_tag(OtherId::TAG);
+        hasher.input_raw(&value.to_le_bytes());
+        assert_eq!(second, OtherId::from(hasher));
+    }
+
+    Ok(())
+}
+
 #[test]
 fn strategy_transparent() -> common::Result {
     #[derive(Wrapper, Clone, PartialEq, Eq, Debug, From)]
@@ -228,6 +280,294 @@ fn conceal() -> common::Result {
     Ok(())
 }
 
+#[test]
+fn strategy_fields() -> common::Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB, tags = order, dumb = { Self::Concealed(0) })]
+    enum Secret {
+        Revealed(u128),
+        Concealed(u8),
+    }
+
+    impl Conceal for Secret {
+        type Concealed = Self;
+        fn conceal(&self) -> Self { Self::Concealed(0xde) }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = fields, id = DumbId)]
+    struct Derived {
+        a: u16,
+        #[commit_encode(skip)]
+        cache: Vec<u8>,
+        #[commit_encode(conceal)]
+        secret: Secret,
+        b: u64,
+    }
+
+    struct Manual {
+        a: u16,
+        secret: Secret,
+        b: u64,
+    }
+
+    impl CommitEncode for Manual {
+        type CommitmentId = DumbId;
+
+        fn commit_encode(&self, e: &mut CommitEngine) {
+            e.commit_to_serialized(&self.a);
+            e.commit_to_concealed(&self.secret);
+            e.commit_to_serialized(&self.b);
+        }
+    }
+
+    let derived = Derived {
+        a: 0xdead,
+        cache: vec![1, 2, 3],
+        secret: Secret::Revealed(0xcafe1234),
+        b: 0xbeefcafebaddafec,
+    };
+    let manual = Manual {
+        a: 0xdead,
+        secret: Secret::Revealed(0xcafe1234),
+        b: 0xbeefcafebaddafec,
+    };
+    assert_eq!(derived.commit_id(), manual.commit_id());
+    assert_eq!(derived.commit().into_layout(), manual.commit().into_layout());
+    assert_eq!(
+        Derived {
+            cache: vec![],
+            ..derived.clone()
+        }
+        .commit_id(),
+        derived.commit_id()
+    );
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = fields, id = DumbId)]
+    struct TaggedInfo(u16, #[commit_encode(skip)] Vec<u8>, u64);
+
+    verify_commit(
+        TaggedInfo(0xdead, vec![1, 2, 3], 0xbeefcafebaddafec),
+        "8506078e6f47e4b75470cb45a18922785f1a54ba4501473b80ba7b0c363d7490",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn strategy_fields_collections() -> common::Result {
+    #[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = strict, id = StrictHash)]
+    struct Inner(u32);
+
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(StrictType)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = fields, id = DumbId)]
+    struct Derived {
+        #[commit_encode(option)]
+        maybe: Option<u32>,
+        #[commit_encode(list)]
+        blob: SmallBlob,
+        #[commit_encode(set)]
+        set: TinyOrdSet<u16>,
+        #[commit_encode(map)]
+        map: TinyOrdMap<u8, u16>,
+        #[commit_encode(hash)]
+        inner: Inner,
+        #[commit_encode(merkle)]
+        leaves: TinyVec<Leaf>,
+    }
+
+    struct Manual(Derived);
+
+    impl CommitEncode for Manual {
+        type CommitmentId = DumbId;
+
+        fn commit_encode(&self, e: &mut CommitEngine) {
+            e.commit_to_option(&self.0.maybe);
+            e.commit_to_list(&self.0.blob);
+            e.commit_to_set(&self.0.set);
+            e.commit_to_map(&self.0.map);
+            e.commit_to_hash(self.0.inner);
+            e.commit_to_merkle(&self.0.leaves);
+        }
+    }
+
+    let derived = Derived {
+        maybe: Some(0xcafe1234),
+        blob: small_vec![1, 2, 3],
+        set: tiny_bset!(1, 2, 3),
+        map: tiny_bmap! { 1 => 0xdead, 2 => 0xbeef },
+        inner: Inner(0xbaddafec),
+        leaves: tiny_vec![Leaf::entropy(1, 0), Leaf::entropy(2, 1), Leaf::entropy(3, 2)],
+    };
+    assert_eq!(derived.commit_id(), Manual(derived.clone()).commit_id());
+    assert_eq!(derived.commit().into_layout(), Manual(derived.clone()).commit().into_layout());
+    assert_ne!(derived.commit_id(), Derived::default().commit_id());
+
+    assert_eq!(
+        Derived::COMMIT_FIELDS
+            .iter()
+            .map(|field| field.kind)
+            .collect::<Vec<_>>(),
+        [
+            CommitFieldKind::Option,
+            CommitFieldKind::List,
+            CommitFieldKind::Set,
+            CommitFieldKind::Map,
+            CommitFieldKind::Hashed,
+            CommitFieldKind::Merklized,
+        ]
+    );
+    assert_eq!(Derived::commit_field_steps().len(), 6);
+
+    Ok(())
+}
+
+#[test]
+fn strategy_fields_layout() -> common::Result {
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(StrictType)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = fields, id = DumbId)]
+    struct Named {
+        a: u16,
+        #[commit_encode(skip)]
+        cache: Vec<u8>,
+        b: u64,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug, Default)]
+    #[derive(StrictType)]
+    #[strict_type(lib = TEST_LIB)]
+    #[derive(CommitEncode)]
+    #[commit_encode(strategy = fields, id = DumbId)]
+    struct Tuple(#[commit_encode(skip)] Vec<u8>, u8);
+
+    assert_eq!(Named::COMMIT_FIELDS, &[
+        CommitField {
+            name: "a",
+            kind: CommitFieldKind::Serialized
+        },
+        CommitField {
+            name: "cache",
+            kind: CommitFieldKind::Skipped
+        },
+        CommitField {
+            name: "b",
+            kind: CommitFieldKind::Serialized
+        },
+    ]);
+    assert_eq!(Tuple::COMMIT_FIELDS, &[
+        CommitField {
+            name: "0",
+            kind: CommitFieldKind::Skipped
+        },
+        CommitField {
+            name: "1",
+            kind: CommitFieldKind::Serialized
+        },
+    ]);
+
+    let steps = Named::commit_field_steps();
+    assert_eq!(steps.iter().map(|(name, _)| *name).collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(
+        steps.into_iter().map(|(_, step)| step).collect::<Vec<_>>(),
+        Named::default().commit().into_layout().into_inner()
+    );
+    assert!(matches!(Tuple::commit_field_steps().as_slice(), [("1", CommitStep::Serialized(_))]));
+
+    Ok(())
+}
+
+#[test]
+fn conceal_derive() -> common::Result {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    enum Secret {
+        Revealed(u128),
+        Concealed(u8),
+    }
+
+    impl Conceal for Secret {
+        type Concealed = Self;
+        fn conceal(&self) -> Self { Self::Concealed(0xde) }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(Conceal)]
+    struct Record {
+        #[conceal(conceal)]
+        secret: Secret,
+        plain: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(Conceal)]
+    struct Tuple(u16, #[conceal(conceal)] Secret);
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct ConcealedSecret([u8; 4]);
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct Opaque(u8);
+
+    impl Conceal for Opaque {
+        type Concealed = ConcealedSecret;
+        fn conceal(&self) -> ConcealedSecret { ConcealedSecret([self.0; 4]) }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct ConcealedData {
+        secret: ConcealedSecret,
+        plain: u64,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(Conceal)]
+    #[conceal(concealed = ConcealedData)]
+    struct Data {
+        #[conceal(conceal)]
+        secret: Opaque,
+        plain: u64,
+    }
+
+    let record = Record {
+        secret: Secret::Revealed(0xcafe1234),
+        plain: vec![1, 2, 3],
+    };
+    assert_eq!(record.conceal(), Record {
+        secret: Secret::Concealed(0xde),
+        plain: vec![1, 2, 3],
+    });
+    assert_eq!(
+        Tuple(0xdead, Secret::Revealed(0)).conceal(),
+        Tuple(0xdead, Secret::Concealed(0xde))
+    );
+    assert_eq!(
+        Data {
+            secret: Opaque(7),
+            plain: 0xbeef,
+        }
+        .conceal(),
+        ConcealedData {
+            secret: ConcealedSecret([7; 4]),
+            plain: 0xbeef,
+        }
+    );
+
+    Ok(())
+}
+
 /* TODO: Refactor
 #[test]
 fn merklize() -> common::Result {
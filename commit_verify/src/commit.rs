@@ -21,7 +21,13 @@
 
 //! Base commit-verify scheme interface.
 
-use crate::CommitmentProtocol;
+use std::borrow::Borrow;
+
+use amplify::confinement::U8;
+
+use crate::{
+    CommitEncode, CommitId, CommitmentId, CommitmentProtocol, DigestExt, Sha256, StrictHash,
+};
 
 /// Error during commitment verification
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
@@ -77,6 +83,69 @@ where Self: Eq + Sized
     }
 }
 
+/// Protocol for commitments to an ordered pair of messages.
+///
+/// The commitment is a [`StrictHash`] tagged with `pair` sub-tag of the
+/// [`StrictHash::TAG`] (see [`crate::subtag`]), computed over length-prefixed
+/// [`CommitId::commit_id`] of the first and then the second message.
+pub struct PairProtocol;
+impl CommitmentProtocol for PairProtocol {}
+
+impl<A, B> CommitVerify<(A, B), PairProtocol> for StrictHash
+where
+    A: CommitEncode,
+    B: CommitEncode,
+    A::CommitmentId: Borrow<[u8]>,
+    B::CommitmentId: Borrow<[u8]>,
+{
+    fn commit((a, b): &(A, B)) -> Self {
+        let mut engine =
+            Sha256::from_subtag(Self::TAG, "pair").expect("sub-tag name has no separator");
+        engine.input_with_len::<U8>(a.commit_id().borrow());
+        engine.input_with_len::<U8>(b.commit_id().borrow());
+        engine.into()
+    }
+}
+
+/// Protocol for commitments to an ordered sequence of messages, binding each
+/// message to its index (see [`commit_sequence`]).
+pub struct SequenceProtocol;
+impl CommitmentProtocol for SequenceProtocol {}
+
+/// Creates commitment to an ordered sequence of messages, such that
+/// reordering of the messages changes the commitment.
+///
+/// The commitment is a [`StrictHash`] tagged with `sequence` sub-tag of the
+/// [`StrictHash::TAG`] (see [`crate::subtag`]), computed over a 32-bit
+/// little-endian index followed by the length-prefixed [`CommitId::commit_id`]
+/// for each of the messages.
+///
+/// # Panics
+///
+/// Panics if the number of messages exceeds `u32::MAX`.
+pub fn commit_sequence<T>(items: &[T]) -> StrictHash
+where
+    T: CommitEncode,
+    T::CommitmentId: Borrow<[u8]>,
+{
+    let mut engine =
+        Sha256::from_subtag(StrictHash::TAG, "sequence").expect("sub-tag name has no separator");
+    for (index, item) in items.iter().enumerate() {
+        let index = u32::try_from(index).expect("too many messages in a sequence");
+        engine.input_raw(&index.to_le_bytes());
+        engine.input_with_len::<U8>(item.commit_id().borrow());
+    }
+    engine.into()
+}
+
+impl<T> CommitVerify<Vec<T>, SequenceProtocol> for StrictHash
+where
+    T: CommitEncode,
+    T::CommitmentId: Borrow<[u8]>,
+{
+    fn commit(items: &Vec<T>) -> Self { commit_sequence(items) }
+}
+
 /// Helpers for writing test functions working with commit-verify scheme
 #[cfg(test)]
 pub(crate) mod test_helpers {
@@ -127,3 +196,42 @@ pub(crate) mod test_helpers {
             });
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mpc::Leaf;
+
+    #[test]
+    fn pair_commitment() {
+        let a = Leaf::entropy(1, 0);
+        let b = Leaf::entropy(2, 1);
+
+        let ab = <StrictHash as CommitVerify<_, PairProtocol>>::commit(&(a, b));
+        let ba = <StrictHash as CommitVerify<_, PairProtocol>>::commit(&(b, a));
+        assert_ne!(ab, ba);
+        assert_eq!(CommitVerify::<_, PairProtocol>::verify(&ab, &(a, b)), Ok(()));
+        assert_eq!(
+            CommitVerify::<_, PairProtocol>::verify(&ab, &(b, a)),
+            Err(VerifyError::InvalidCommitment)
+        );
+        assert_ne!(ab, <StrictHash as CommitVerify<_, PairProtocol>>::commit(&(a, a)));
+    }
+
+    #[test]
+    fn sequence_commitment() {
+        let a = Leaf::entropy(1, 0);
+        let b = Leaf::entropy(2, 1);
+
+        let ab = commit_sequence(&[a, b]);
+        assert_ne!(ab, commit_sequence(&[b, a]));
+        assert_ne!(ab, commit_sequence(&[a]));
+        assert_ne!(commit_sequence::<Leaf>(&[]), commit_sequence(&[a]));
+        assert_ne!(ab, <StrictHash as CommitVerify<_, PairProtocol>>::commit(&(a, b)));
+        assert_eq!(CommitVerify::<_, SequenceProtocol>::verify(&ab, &vec![a, b]), Ok(()));
+        assert_eq!(
+            CommitVerify::<_, SequenceProtocol>::verify(&ab, &vec![b, a]),
+            Err(VerifyError::InvalidCommitment)
+        );
+    }
+}
@@ -21,6 +21,8 @@
 
 //! Convolved commitments (convolve-commit-verify scheme).
 
+use std::marker::PhantomData;
+
 use crate::{CommitmentProtocol, VerifyEq};
 
 /// Error during commitment verification
@@ -60,6 +62,16 @@ where
     /// Extract supplement from the proof.
     fn extract_supplement(&self) -> &Self::Suppl;
 
+    /// Converts the proof into its canonical form, such that all logically
+    /// equivalent proofs (for instance, the ones having different encodings of
+    /// the same supplement) become equal. Returns `None` if the proof is
+    /// already canonical.
+    ///
+    /// Applied to both proofs before their [`VerifyEq`] comparison during the
+    /// verification. Default implementation treats all proofs as canonical,
+    /// so they are compared as is.
+    fn canonicalize(&self) -> Option<Self> { None }
+
     /// Verifies commitment using proof (the `self`) against the message.
     ///
     /// Default implementation repeats [`ConvolveCommit::convolve_commit`]
@@ -79,12 +91,90 @@ where
     where
         Self: VerifyEq,
     {
+        self.verify_with_supplement(self.extract_supplement(), msg, commitment)
+    }
+
+    /// Verifies the proof (the `self`) against the message and each of the
+    /// candidate `commitments`, returning index of the first matching
+    /// commitment, or `None` if none of them match.
+    ///
+    /// # Errors
+    ///
+    /// Errors if verification against some of the candidates fails for a
+    /// reason other than [`ConvolveVerifyError::CommitmentMismatch`].
+    fn verify_any(
+        &self,
+        msg: &Msg,
+        commitments: &[Source::Commitment],
+    ) -> Result<Option<usize>, ConvolveVerifyError>
+    where
+        Self: VerifyEq,
+    {
+        for (index, commitment) in commitments.iter().enumerate() {
+            match self.verify(msg, commitment) {
+                Ok(()) => return Ok(Some(index)),
+                Err(ConvolveVerifyError::CommitmentMismatch) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Verifies a batch of proofs against their messages and commitments,
+    /// stopping at the first failure.
+    ///
+    /// Default implementation verifies each item with
+    /// [`ConvolveCommitProof::verify`]; specific implementations may override
+    /// it to amortize the setup shared by all the items (like tagged hasher
+    /// initialization).
+    ///
+    /// # Errors
+    ///
+    /// Errors with the index of the first item which doesn't pass the
+    /// validation and the reason of the failure (see [`ConvolveVerifyError`]).
+    fn verify_batch(
+        items: &[(&Self, &Msg, &Source::Commitment)],
+    ) -> Result<(), (usize, ConvolveVerifyError)>
+    where Self: VerifyEq {
+        for (index, (proof, msg, commitment)) in items.iter().enumerate() {
+            proof.verify(msg, commitment).map_err(|err| (index, err))?;
+        }
+        Ok(())
+    }
+
+    /// Verifies commitment using proof (the `self`) and an explicitly provided
+    /// `suppl` supplement against the message.
+    ///
+    /// Works like [`ConvolveCommitProof::verify`], but instead of extracting
+    /// supplement from the proof uses the provided one.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the commitment doesn't pass the validation (see
+    /// [`ConvolveVerifyError`] variants for the cases when this may happen).
+    fn verify_with_supplement(
+        &self,
+        suppl: &Self::Suppl,
+        msg: &Msg,
+        commitment: &Source::Commitment,
+    ) -> Result<(), ConvolveVerifyError>
+    where
+        Self: VerifyEq,
+    {
+        #[cfg(feature = "metrics")]
+        crate::metrics::count_convolve_verification();
+
         let original = self.restore_original(commitment);
-        let suppl = self.extract_supplement();
         let (commitment_prime, proof) = original
             .convolve_commit(suppl, msg)
             .map_err(|_| ConvolveVerifyError::ImpossibleMessage)?;
-        if !self.verify_eq(&proof) {
+        let canonical = self.canonicalize();
+        let canonical_prime = proof.canonicalize();
+        if !canonical
+            .as_ref()
+            .unwrap_or(self)
+            .verify_eq(canonical_prime.as_ref().unwrap_or(&proof))
+        {
             return Err(ConvolveVerifyError::InvalidProof);
         }
         if !commitment.verify_eq(&commitment_prime) {
@@ -94,6 +184,42 @@ where
     }
 }
 
+/// Proof for [`ConvolveCommit`] protocol which supplement may be re-derived
+/// from some external context (for instance, on-chain data) during the
+/// verification, such that it doesn't have to be stored and distributed.
+pub trait SupplementSource<Msg, Source, Protocol>:
+    ConvolveCommitProof<Msg, Source, Protocol>
+where
+    Source: ConvolveCommit<Msg, Self, Protocol>,
+    Protocol: CommitmentProtocol,
+{
+    /// Context from which the supplement is derived.
+    type Context;
+
+    /// Derives supplement from the provided `context`.
+    fn derive_supplement(&self, context: &Self::Context) -> Self::Suppl;
+
+    /// Verifies commitment using proof (the `self`) against the message,
+    /// re-deriving the supplement from the `context`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the commitment doesn't pass the validation (see
+    /// [`ConvolveVerifyError`] variants for the cases when this may happen).
+    fn verify_with_context(
+        &self,
+        context: &Self::Context,
+        msg: &Msg,
+        commitment: &Source::Commitment,
+    ) -> Result<(), ConvolveVerifyError>
+    where
+        Self: VerifyEq,
+    {
+        let suppl = self.derive_supplement(context);
+        self.verify_with_supplement(&suppl, msg, commitment)
+    }
+}
+
 /// Trait for *convolve-commit-verify scheme*, where some data structure (named
 /// *container*) may commit to existing *message* using *supplement* and
 /// producing final *commitment* value. The commitment can't be used to restore
@@ -106,6 +232,7 @@ where
 /// commitment to a *message* and a *proof*:
 /// - `self + supplement -> internal_repr`;
 /// - `internal_repr + msg -> (commitment, proof)`.
+///
 /// Later on, a verifier presented with a message and the proof may do the
 /// commitment verification in the following way:
 /// `msg, proof, commitment -> bool`.
@@ -187,6 +314,10 @@ where
     ///
     /// Implementations must error with a dedicated error type enumerating
     /// commitment procedure mistakes.
+    ///
+    /// An empty message is a valid message: implementations must produce a
+    /// deterministic commitment to it, which differs from commitments to any
+    /// non-empty message (for instance, by committing to the message length).
     fn convolve_commit(
         &self,
         supplement: &Proof::Suppl,
@@ -203,3 +334,127 @@ where
         unimplemented!("EmbedCommitVerify::_phantom is a marker method which must not be used")
     }
 }
+
+/// Aggregated proof for multiple messages committed with the same container
+/// and supplement, keeping the original container and the supplement only
+/// once.
+///
+/// Each of the commitments is verified by repeating
+/// [`ConvolveCommit::convolve_commit`] procedure for the original container
+/// and the supplement; unlike verification of individual
+/// [`ConvolveCommitProof`]s it doesn't need to restore the original container
+/// for each of the messages.
+pub struct AggregateProof<Msg, Source, Proof, Protocol>
+where
+    Source: ConvolveCommit<Msg, Proof, Protocol>,
+    Proof: ConvolveCommitProof<Msg, Source, Protocol>,
+    Protocol: CommitmentProtocol,
+{
+    original: Source,
+    supplement: Proof::Suppl,
+    _phantom: PhantomData<fn(&Msg, Protocol)>,
+}
+
+impl<Msg, Source, Proof, Protocol> AggregateProof<Msg, Source, Proof, Protocol>
+where
+    Source: ConvolveCommit<Msg, Proof, Protocol>,
+    Proof: ConvolveCommitProof<Msg, Source, Protocol>,
+    Protocol: CommitmentProtocol,
+{
+    /// Constructs aggregated proof out of the original container and the
+    /// supplement used for the commitments.
+    pub fn new(original: Source, supplement: Proof::Suppl) -> Self {
+        Self {
+            original,
+            supplement,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Commits to each of the `msgs` with the `original` container and the
+    /// `supplement`, returning the aggregated proof and the commitments in
+    /// the order of the messages.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ConvolveCommit::CommitError`] if the commitment to some
+    /// of the messages can't be created.
+    pub fn commit(
+        original: Source,
+        supplement: Proof::Suppl,
+        msgs: &[Msg],
+    ) -> Result<(Self, Vec<Source::Commitment>), Source::CommitError> {
+        let proof = Self::new(original, supplement);
+        let commitments = msgs
+            .iter()
+            .map(|msg| {
+                proof
+                    .original
+                    .convolve_commit(&proof.supplement, msg)
+                    .map(|(commitment, _)| commitment)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((proof, commitments))
+    }
+
+    /// Returns the original container.
+    pub fn original(&self) -> &Source { &self.original }
+
+    /// Returns the supplement shared by all of the commitments.
+    pub fn supplement(&self) -> &Proof::Suppl { &self.supplement }
+
+    /// Verifies all of the `items`, each consisting of a message and a
+    /// commitment to it, stopping at the first failure.
+    ///
+    /// # Errors
+    ///
+    /// Errors with the index of the first item which doesn't pass the
+    /// validation and the reason of the failure: either
+    /// [`ConvolveVerifyError::ImpossibleMessage`], if the commitment to the
+    /// message can't be created, or
+    /// [`ConvolveVerifyError::CommitmentMismatch`].
+    pub fn verify_all(
+        &self,
+        items: &[(&Msg, &Source::Commitment)],
+    ) -> Result<(), (usize, ConvolveVerifyError)> {
+        for (index, (msg, commitment)) in items.iter().enumerate() {
+            let (commitment_prime, _) = self
+                .original
+                .convolve_commit(&self.supplement, msg)
+                .map_err(|_| (index, ConvolveVerifyError::ImpossibleMessage))?;
+            if !commitment.verify_eq(&commitment_prime) {
+                return Err((index, ConvolveVerifyError::CommitmentMismatch));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extension of [`ConvolveCommit`] for protocols layering additional proofs
+/// (for instance, zero-knowledge ones) atop of the commitment, which require
+/// knowledge of the values used to produce the commitment.
+///
+/// Containers not providing any such values should use `()` as
+/// [`Self::OpeningHint`].
+pub trait ConvolveCommitHint<Msg, Proof, Protocol>: ConvolveCommit<Msg, Proof, Protocol>
+where
+    Proof: ConvolveCommitProof<Msg, Self, Protocol>,
+    Protocol: CommitmentProtocol,
+{
+    /// Opening hint, exposing blinding or supplement-derived values used
+    /// during the commitment procedure.
+    type OpeningHint;
+
+    /// Works like [`ConvolveCommit::convolve_commit`], additionally returning
+    /// the hint which may be used to open the commitment.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ConvolveCommit::CommitError`] in the same cases as
+    /// [`ConvolveCommit::convolve_commit`].
+    fn convolve_commit_with_hint(
+        &self,
+        supplement: &Proof::Suppl,
+        msg: &Msg,
+    ) -> Result<(Self::Commitment, Proof, Self::OpeningHint), Self::CommitError>;
+}
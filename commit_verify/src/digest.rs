@@ -19,29 +19,114 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use amplify::confinement::{U32, U8};
 use amplify::num::u24;
 pub use ripemd::Ripemd160;
-pub use sha2::{Digest, Sha256};
+pub use sha2::{Digest, Sha256, Sha512_256};
+use subtle::ConstantTimeEq;
+
+/// Separator between parent tag and a child name in the composed sub-tags.
+pub const SUBTAG_SEPARATOR: char = '/';
+
+/// sub-tag child name `{0}` contains sub-tag separator, which makes the tag
+/// composition ambiguous.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub struct SubtagError(String);
+
+/// Errors inputting length-prefixed data into a hasher (see
+/// [`DigestExt::try_input_with_len`]).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum LenPrefixError {
+    /// data length {len} exceeds the maximum of {max} bytes.
+    TooLarge { len: usize, max: usize },
+
+    /// length prefix for data of up to {0} bytes is not supported.
+    UnsupportedMax(usize),
+}
+
+/// Errors adding a section to [`TaggedCommitBuilder`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SectionError {
+    /// section name `{0}` exceeds 255 bytes.
+    NameTooLong(String),
+
+    /// data of section `{name}` have {len} bytes, exceeding 2^32-1 bytes limit.
+    DataTooLarge { name: String, len: usize },
+}
+
+/// Composes hierarchical tag for a `child` sub-protocol of a protocol tagged
+/// with `parent` tag, in form of `parent/child`.
+///
+/// # Errors
+///
+/// Errors if the `child` name contains [`SUBTAG_SEPARATOR`], since it would
+/// make the composition ambiguous.
+pub fn subtag(parent: &str, child: &str) -> Result<String, SubtagError> {
+    if child.contains(SUBTAG_SEPARATOR) {
+        return Err(SubtagError(child.to_owned()));
+    }
+    Ok(format!("{parent}{SUBTAG_SEPARATOR}{child}"))
+}
 
 pub trait DigestExt<const BYTE_LEN: usize = 32>: Digest {
     fn from_tag(tag: impl AsRef<[u8]>) -> Self;
+    /// Constructs hasher tagged with the composed [`subtag`] of the `parent`
+    /// tag and `child` name.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `child` name contains [`SUBTAG_SEPARATOR`].
+    fn from_subtag(parent: &str, child: &str) -> Result<Self, SubtagError>
+    where Self: Sized {
+        subtag(parent, child).map(Self::from_tag)
+    }
     fn input_raw(&mut self, data: &[u8]);
+    /// Inputs the `data` prefixed with their length, encoded with the minimal
+    /// number of bytes able to hold `MAX`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data are longer than `MAX` or if `MAX` exceeds
+    /// `u32::MAX` (see [`DigestExt::try_input_with_len`]).
     fn input_with_len<const MAX: usize>(&mut self, data: &[u8]) {
+        self.try_input_with_len::<MAX>(data)
+            .expect("data too large")
+    }
+    /// Inputs the `data` prefixed with their length, encoded with the minimal
+    /// number of bytes able to hold `MAX`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the data are longer than `MAX` or if `MAX` exceeds
+    /// `u32::MAX`; in this case nothing is input into the hasher.
+    fn try_input_with_len<const MAX: usize>(&mut self, data: &[u8]) -> Result<(), LenPrefixError> {
         let len = data.len();
+        if MAX > 0xFFFFFFFF {
+            return Err(LenPrefixError::UnsupportedMax(MAX));
+        }
+        if len > MAX {
+            return Err(LenPrefixError::TooLarge { len, max: MAX });
+        }
         match MAX {
             0..=0xFF => self.input_raw(&(len as u8).to_le_bytes()),
             0x100..=0xFFFF => self.input_raw(&(len as u16).to_le_bytes()),
             0x10000..=0xFFFFFF => self.input_raw(&u24::with(len as u32).to_le_bytes()),
-            0x1000000..=0xFFFFFFFF => self.input_raw(&(len as u32).to_le_bytes()),
-            _ => panic!("data too large"),
+            _ => self.input_raw(&(len as u32).to_le_bytes()),
         }
         self.input_raw(data);
+        Ok(())
     }
     fn finish(self) -> [u8; BYTE_LEN];
 }
 
 impl DigestExt for Sha256 {
     fn from_tag(tag: impl AsRef<[u8]>) -> Self {
+        #[cfg(feature = "metrics")]
+        crate::metrics::count_tagged_hash();
+
         let mut tagger = Sha256::default();
         tagger.update(tag);
         let tag = tagger.finalize();
@@ -57,8 +142,83 @@ impl DigestExt for Sha256 {
     fn finish(self) -> [u8; 32] { self.finalize().into() }
 }
 
+/// Builder for a tagged commitment to an ordered sequence of named sections.
+///
+/// Each section is committed to as its length-prefixed name followed by the
+/// length-prefixed data, such that changing the order, the names or the
+/// boundaries of the sections changes the resulting commitment.
+#[derive(Clone, Debug)]
+pub struct TaggedCommitBuilder {
+    engine: Sha256,
+}
+
+impl TaggedCommitBuilder {
+    /// Starts the commitment tagged with the provided `tag`.
+    pub fn new(tag: impl AsRef<[u8]>) -> Self {
+        Self {
+            engine: Sha256::from_tag(tag),
+        }
+    }
+
+    /// Adds a section named `name` with the `data`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the section name exceeds 255 bytes or the data exceed
+    /// 2^32-1 bytes.
+    pub fn section(mut self, name: &str, data: impl AsRef<[u8]>) -> Result<Self, SectionError> {
+        let data = data.as_ref();
+        if name.len() > U8 {
+            return Err(SectionError::NameTooLong(name.to_owned()));
+        }
+        if data.len() > U32 {
+            return Err(SectionError::DataTooLarge {
+                name: name.to_owned(),
+                len: data.len(),
+            });
+        }
+        self.engine.input_with_len::<U8>(name.as_bytes());
+        self.engine.input_with_len::<U32>(data);
+        Ok(self)
+    }
+
+    /// Completes the commitment, returning the final digest.
+    pub fn finalize(self) -> [u8; 32] { self.engine.finish() }
+}
+
+/// SHA256 hasher initialized with a tag once (see [`DigestExt::from_tag`]),
+/// which can be reused for computing many tagged hashes with the same tag
+/// without re-hashing the tag each time.
+#[derive(Clone, Debug)]
+pub struct TaggedEngine {
+    engine: Sha256,
+}
+
+impl TaggedEngine {
+    /// Initializes hasher with the `tag`.
+    pub fn with_tag(tag: impl AsRef<[u8]>) -> Self {
+        Self {
+            engine: Sha256::from_tag(tag),
+        }
+    }
+
+    /// Returns a copy of the tagged hasher, ready to take the data.
+    pub fn engine(&self) -> Sha256 { self.engine.clone() }
+
+    /// Computes tagged hash of the `data`, equal to the hash produced by the
+    /// hasher from [`DigestExt::from_tag`] with the same tag.
+    pub fn hash(&self, data: impl AsRef<[u8]>) -> [u8; 32] {
+        let mut engine = self.engine();
+        engine.input_raw(data.as_ref());
+        engine.finish()
+    }
+}
+
 impl DigestExt<20> for Ripemd160 {
     fn from_tag(tag: impl AsRef<[u8]>) -> Self {
+        #[cfg(feature = "metrics")]
+        crate::metrics::count_tagged_hash();
+
         let mut tagger = Ripemd160::default();
         tagger.update(tag);
         let tag = tagger.finalize();
@@ -73,3 +233,212 @@ impl DigestExt<20> for Ripemd160 {
 
     fn finish(self) -> [u8; 20] { self.finalize().into() }
 }
+
+impl DigestExt for Sha512_256 {
+    fn from_tag(tag: impl AsRef<[u8]>) -> Self {
+        #[cfg(feature = "metrics")]
+        crate::metrics::count_tagged_hash();
+
+        let mut tagger = Sha512_256::default();
+        tagger.update(tag);
+        let tag = tagger.finalize();
+
+        let mut engine = Sha512_256::default();
+        engine.update(tag);
+        engine.update(tag);
+        engine
+    }
+
+    fn input_raw(&mut self, data: &[u8]) { self.update(data); }
+
+    fn finish(self) -> [u8; 32] { self.finalize().into() }
+}
+
+/// Compares two byte strings in constant time with [`ConstantTimeEq`], such
+/// that for the strings of the same length the time of comparison doesn't
+/// depend on the data. Strings of different length are never equal.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool { a.ct_eq(b).into() }
+
+#[cfg(feature = "bitcoin_hashes")]
+mod _sha256t {
+    use core::marker::PhantomData;
+
+    use bitcoin_hashes::{sha256, sha256t, Hash, HashEngine};
+
+    use crate::{mpc, CommitmentId, MerkleHash, StrictHash};
+
+    /// [`sha256t::Tag`] using the tag of a [`CommitmentId`] type.
+    ///
+    /// Tagged hashes produced by [`DigestExt::from_tag`] for [`Sha256`] follow
+    /// the same BIP-340 construction as `bitcoin_hashes::sha256t`, so
+    /// `sha256t::Hash<CommitmentTag<Id>>` is byte-equal to the `Id` computed
+    /// over the same data.
+    #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Default)]
+    pub struct CommitmentTag<Id: CommitmentId>(PhantomData<Id>);
+
+    impl<Id: CommitmentId> sha256t::Tag for CommitmentTag<Id> {
+        fn engine() -> sha256::HashEngine {
+            let tag = sha256::Hash::hash(Id::TAG.as_bytes());
+            let mut engine = sha256::Hash::engine();
+            engine.input(tag.as_ref());
+            engine.input(tag.as_ref());
+            engine
+        }
+    }
+
+    macro_rules! impl_sha256t {
+        ($($id:ty),+) => {$(
+            impl From<sha256t::Hash<CommitmentTag<$id>>> for $id {
+                fn from(hash: sha256t::Hash<CommitmentTag<$id>>) -> Self {
+                    hash.to_byte_array().into()
+                }
+            }
+
+            impl From<$id> for sha256t::Hash<CommitmentTag<$id>> {
+                fn from(id: $id) -> Self { Self::from_byte_array(id.to_byte_array()) }
+            }
+        )+};
+    }
+
+    impl_sha256t!(StrictHash, MerkleHash, mpc::Commitment);
+}
+#[cfg(feature = "bitcoin_hashes")]
+pub use _sha256t::CommitmentTag;
+
+#[cfg(test)]
+mod test {
+    use amplify::hex::ToHex;
+
+    use super::*;
+    use crate::{mpc, CommitmentId, MerkleHash, StrictHash, VerifyEq};
+
+    fn tagged_hash(mut engine: Sha256, data: &[u8]) -> [u8; 32] {
+        engine.input_raw(data);
+        engine.finish()
+    }
+
+    #[test]
+    fn subtags() {
+        assert_eq!(subtag("a", "b").unwrap(), "a/b");
+        assert_eq!(subtag(StrictHash::TAG, "child").unwrap(), format!("{}/child", StrictHash::TAG));
+
+        let data = b"data";
+        let ab = tagged_hash(Sha256::from_subtag("a", "b").unwrap(), data);
+        assert_eq!(ab, tagged_hash(Sha256::from_tag("a/b"), data));
+        assert_eq!(ab, tagged_hash(Sha256::from_subtag("a", "b").unwrap(), data));
+        assert_ne!(ab, tagged_hash(Sha256::from_subtag("a", "c").unwrap(), data));
+        assert_ne!(ab, tagged_hash(Sha256::from_tag("ab"), data));
+    }
+
+    #[test]
+    fn section_commitment() {
+        let commit = |sections: &[(&str, &[u8])]| {
+            sections
+                .iter()
+                .try_fold(TaggedCommitBuilder::new("test"), |builder, (name, data)| {
+                    builder.section(name, data)
+                })
+                .unwrap()
+                .finalize()
+        };
+
+        let ordered = commit(&[("inputs", b"in"), ("outputs", b"out")]);
+        assert_eq!(ordered, commit(&[("inputs", b"in"), ("outputs", b"out")]));
+        assert_ne!(ordered, commit(&[("outputs", b"out"), ("inputs", b"in")]));
+        assert_ne!(ordered, commit(&[("inputs", b"in"), ("outputs", b"ou"), ("t", b"")]));
+        assert_ne!(ordered, commit(&[("inputs", b"inout")]));
+
+        let name = "n".repeat(256);
+        assert_eq!(
+            TaggedCommitBuilder::new("test").section(&name, b"").err(),
+            Some(SectionError::NameTooLong(name))
+        );
+    }
+
+    #[test]
+    fn length_prefix() {
+        let mut engine = Sha256::default();
+        assert_eq!(
+            engine.try_input_with_len::<U8>(&[0u8; 256]),
+            Err(LenPrefixError::TooLarge { len: 256, max: U8 })
+        );
+        assert_eq!(
+            engine.try_input_with_len::<0x1_0000_0000>(b""),
+            Err(LenPrefixError::UnsupportedMax(0x1_0000_0000))
+        );
+        // Failed inputs leave the hasher untouched
+        assert_eq!(engine.clone().finish(), Sha256::default().finish());
+
+        assert_eq!(engine.try_input_with_len::<U8>(b"data"), Ok(()));
+        let mut prefixed = Sha256::default();
+        prefixed.input_raw(b"\x04data");
+        assert_eq!(engine.finish(), prefixed.finish());
+    }
+
+    #[test]
+    fn subtag_ambiguous() {
+        assert_eq!(subtag("a", "b/c"), Err(SubtagError(s!("b/c"))));
+        assert_eq!(Sha256::from_subtag("a", "b/c").err(), Some(SubtagError(s!("b/c"))));
+    }
+
+    #[test]
+    #[cfg(feature = "bitcoin_hashes")]
+    fn sha256t_interop() {
+        use bitcoin_hashes::{sha256t, Hash};
+
+        let data = b"client-side validation";
+
+        let mut engine = Sha256::from_tag(StrictHash::TAG);
+        engine.input_raw(data);
+        let id = StrictHash::from(engine);
+
+        let hash = sha256t::Hash::<CommitmentTag<StrictHash>>::hash(data);
+        assert_eq!(hash.to_byte_array(), id.to_byte_array());
+        assert_eq!(StrictHash::from(hash), id);
+        assert_eq!(sha256t::Hash::<CommitmentTag<StrictHash>>::from(id), hash);
+    }
+
+    #[test]
+    fn constant_time_eq() {
+        assert!(ct_eq(b"", b""));
+        assert!(ct_eq(&[0xde, 0xad], &[0xde, 0xad]));
+        assert!(!ct_eq(&[0xde, 0xad], &[0xde, 0xaf]));
+        assert!(!ct_eq(&[0xde, 0xad], &[0xde]));
+
+        let hash1 = StrictHash::from([1u8; 32]);
+        let mut bytes = [1u8; 32];
+        assert!(hash1.verify_eq(&StrictHash::from(bytes)));
+        bytes[31] = 0;
+        assert!(!hash1.verify_eq(&StrictHash::from(bytes)));
+        assert_ne!(MerkleHash::from(bytes), MerkleHash::from([1u8; 32]));
+        assert_eq!(mpc::Commitment::from(bytes), mpc::Commitment::from(bytes));
+    }
+
+    #[test]
+    fn tagged_engine() {
+        let engine = TaggedEngine::with_tag(MerkleHash::TAG);
+        for len in 0..200u8 {
+            let data = (0..len).collect::<Vec<_>>();
+            assert_eq!(engine.hash(&data), tagged_hash(Sha256::from_tag(MerkleHash::TAG), &data));
+        }
+        let mut hasher = engine.engine();
+        hasher.input_raw(b"data");
+        assert_eq!(hasher.finish(), engine.hash(b"data"));
+    }
+
+    #[test]
+    fn sha512_256_tagged() {
+        let mut engine = Sha512_256::from_tag("urn:test:tag");
+        engine.input_raw(b"data");
+        assert_eq!(
+            engine.finish().to_hex(),
+            "e921f3ffdd08c32093a1aa4e83f6a619779e71a97d010f3ff76245dd04f25b5a"
+        );
+        assert_ne!(
+            tagged_hash(Sha256::from_tag("urn:test:tag"), b"data"),
+            Sha512_256::from_tag("urn:test:tag")
+                .chain_update(b"data")
+                .finish()
+        );
+    }
+}
@@ -48,6 +48,12 @@ pub enum EmbedVerifyError<E: std::error::Error> {
 /// Trait for equivalence verification. Implemented for all types implementing
 /// `Eq`. For non-`Eq` types this trait provides way to implement custom
 /// equivalence verification used during commitment verification procedure.
+///
+/// Commitment hash types of this crate ([`crate::StrictHash`],
+/// [`crate::MerkleHash`], [`crate::MerkleShapeHash`] and
+/// [`crate::mpc::Commitment`]) implement `Eq`, and thus this trait, with a
+/// constant-time comparison (see [`crate::ct_eq`]). Other types use their `Eq`
+/// implementation, which usually is not constant-time.
 pub trait VerifyEq {
     /// Verifies commit-equivalence of two instances of the same type.
     fn verify_eq(&self, other: &Self) -> bool;
@@ -166,6 +172,9 @@ where
         Self: VerifyEq,
         Self::Proof: VerifyEq,
     {
+        #[cfg(feature = "metrics")]
+        crate::metrics::count_embed_verification();
+
         let mut container_prime = proof.restore_original_container(self)?;
         let proof_prime = container_prime.embed_commit(msg)?;
         if !proof_prime.verify_eq(proof) {
@@ -258,6 +267,10 @@ pub(crate) mod test_helpers {
         Source::Commitment: Clone + Debug + Hash + VerifyEq + Eq,
         [u8; 32]: ConvolveCommitProof<Msg, Source, TestProtocol, Suppl = [u8; 32]>,
     {
+        assert!(
+            messages.iter().any(|msg| msg.as_ref().is_empty()),
+            "commitment to an empty message must be tested"
+        );
         messages.iter().fold(
             HashSet::<Source::Commitment>::with_capacity(messages.len()),
             |mut acc, msg| {
@@ -301,13 +314,17 @@ mod test {
     use core::fmt::Debug;
 
     use amplify::confinement::{SmallBlob, SmallVec, U32};
+    use amplify::hex::{FromHex, ToHex};
     use sha2::Sha256;
 
     use super::test_helpers::*;
     use super::*;
     use crate::digest::DigestExt;
     use crate::test_helpers::gen_messages;
-    use crate::{ConvolveCommit, ConvolveCommitProof};
+    use crate::{
+        AggregateProof, ConvolveCommit, ConvolveCommitHint, ConvolveCommitProof,
+        ConvolveVerifyError, SupplementSource,
+    };
 
     #[derive(Clone, PartialEq, Eq, Debug, Hash, Error, Display)]
     #[display("error")]
@@ -372,11 +389,250 @@ mod test {
         fn extract_supplement(&self) -> &Self::Suppl { self }
     }
 
+    impl<T> SupplementSource<T, DummyVec, TestProtocol> for [u8; 32]
+    where T: AsRef<[u8]> + Clone
+    {
+        type Context = u8;
+
+        fn derive_supplement(&self, context: &u8) -> Self::Suppl { [*context; 32] }
+    }
+
+    /// Opening hint exposing the blinding value used by [`DummyVec`]
+    /// convolve-commitment.
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    struct DummyHint {
+        blinding: [u8; 32],
+    }
+
+    impl DummyHint {
+        /// Downstream opener, re-creating commitment out of the hint.
+        fn open(&self, msg: &[u8]) -> [u8; 32] {
+            let mut engine = Sha256::default();
+            engine.input_raw(&self.blinding);
+            engine.input_with_len::<U32>(msg);
+            engine.finish()
+        }
+    }
+
+    impl<T> ConvolveCommitHint<T, [u8; 32], TestProtocol> for DummyVec
+    where T: AsRef<[u8]> + Clone
+    {
+        type OpeningHint = DummyHint;
+
+        fn convolve_commit_with_hint(
+            &self,
+            supplement: &[u8; 32],
+            msg: &T,
+        ) -> Result<(Self::Commitment, [u8; 32], DummyHint), Self::CommitError> {
+            let (commitment, proof) = self.convolve_commit(supplement, msg)?;
+            Ok((commitment, proof, DummyHint {
+                blinding: *supplement,
+            }))
+        }
+    }
+
+    /// Proof keeping hex-encoded supplement, which may be written both in
+    /// lower and upper case.
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct HexProof(String);
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct HexContainer;
+
+    impl<T> ConvolveCommit<T, HexProof, TestProtocol> for HexContainer
+    where T: AsRef<[u8]> + Clone
+    {
+        type Commitment = [u8; 32];
+        type CommitError = Error;
+
+        fn convolve_commit(
+            &self,
+            supplement: &HexProof,
+            msg: &T,
+        ) -> Result<(Self::Commitment, HexProof), Self::CommitError> {
+            let supplement = Vec::<u8>::from_hex(&supplement.0).map_err(|_| Error)?;
+            let mut engine = Sha256::default();
+            engine.input_raw(&supplement);
+            engine.input_with_len::<U32>(msg.as_ref());
+            Ok((engine.finish(), HexProof(supplement.to_hex())))
+        }
+    }
+
+    impl<T> ConvolveCommitProof<T, HexContainer, TestProtocol> for HexProof
+    where T: AsRef<[u8]> + Clone
+    {
+        type Suppl = HexProof;
+
+        fn restore_original(&self, _: &[u8; 32]) -> HexContainer { HexContainer }
+
+        fn extract_supplement(&self) -> &Self::Suppl { self }
+
+        fn canonicalize(&self) -> Option<Self> { Some(HexProof(self.0.to_lowercase())) }
+    }
+
+    #[test]
+    fn test_convolve_canonical_proof() {
+        let msg = SmallVec::<u8>::try_from(b"message".to_vec()).unwrap();
+        let upper = HexProof(SUPPLEMENT.to_hex().to_uppercase());
+        let (commitment, proof) = HexContainer.convolve_commit(&upper, &msg).unwrap();
+        assert_ne!(proof, upper);
+        assert_eq!(
+            ConvolveCommitProof::<SmallVec<u8>, HexContainer, _>::canonicalize(&upper),
+            Some(proof.clone())
+        );
+        assert_eq!(
+            ConvolveCommitProof::<_, HexContainer, _>::verify(&upper, &msg, &commitment),
+            Ok(())
+        );
+        assert_eq!(
+            ConvolveCommitProof::<_, HexContainer, _>::verify(&proof, &msg, &commitment),
+            Ok(())
+        );
+    }
+
     #[test]
     fn test_embed_commit() {
         embed_commit_verify_suite::<SmallVec<u8>, DummyVec>(gen_messages(), DummyVec(default!()));
     }
 
+    #[test]
+    fn test_convolve_supplement_source() {
+        let container = DummyVec(small_vec![0xC0; 15]);
+        for msg in gen_messages() {
+            let (commitment, proof) = container.convolve_commit(&SUPPLEMENT, &msg).unwrap();
+            let stored = ConvolveCommitProof::<_, DummyVec, _>::verify(&proof, &msg, &commitment);
+            let derived = proof.verify_with_context(&0xFF, &msg, &commitment);
+            assert_eq!(stored, Ok(()));
+            assert_eq!(derived, stored);
+            assert_eq!(
+                proof.verify_with_context(&0xFE, &msg, &commitment),
+                Err(ConvolveVerifyError::InvalidProof)
+            );
+        }
+    }
+
+    #[test]
+    fn test_convolve_verify_any() {
+        let container = DummyVec(small_vec![0xC0; 15]);
+        let msg = small_vec![0x01, 0x02];
+        let commit = |msg: &SmallVec<u8>| container.convolve_commit(&SUPPLEMENT, msg).unwrap().0;
+        let proof = SUPPLEMENT;
+
+        let candidates = [commit(&small_vec![0x01]), commit(&msg), commit(&small_vec![0x02])];
+        assert_eq!(
+            ConvolveCommitProof::<_, DummyVec, _>::verify_any(&proof, &msg, &candidates),
+            Ok(Some(1))
+        );
+        assert_eq!(
+            ConvolveCommitProof::<_, DummyVec, _>::verify_any(&proof, &msg, &candidates[2..]),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_convolve_commit_hint() {
+        let container = DummyVec(default!());
+        for msg in gen_messages() {
+            let (commitment, proof, hint) = container
+                .convolve_commit_with_hint(&SUPPLEMENT, &msg)
+                .unwrap();
+            assert_eq!((commitment, proof), container.convolve_commit(&SUPPLEMENT, &msg).unwrap());
+            assert_eq!(hint.open(msg.as_ref()), commitment);
+            assert_ne!(hint.open(b"other"), commitment);
+        }
+    }
+
+    #[test]
+    fn test_convolve_aggregate_proof() {
+        let container = DummyVec(small_vec![0xC0; 15]);
+        let msgs = gen_messages().into_iter().take(3).collect::<Vec<_>>();
+        let (aggregate, commitments) =
+            AggregateProof::<_, _, [u8; 32], _>::commit(container.clone(), SUPPLEMENT, &msgs)
+                .unwrap();
+        assert_eq!(aggregate.original(), &container);
+        assert_eq!(aggregate.supplement(), &SUPPLEMENT);
+
+        let mut items = msgs.iter().zip(&commitments).collect::<Vec<_>>();
+        assert_eq!(aggregate.verify_all(&items), Ok(()));
+        for (msg, commitment) in &items {
+            let (_, proof) = container.convolve_commit(&SUPPLEMENT, *msg).unwrap();
+            assert_eq!(
+                ConvolveCommitProof::<_, DummyVec, _>::verify(&proof, *msg, commitment),
+                Ok(())
+            );
+        }
+
+        items[1].1 = &commitments[2];
+        assert_eq!(aggregate.verify_all(&items), Err((1, ConvolveVerifyError::CommitmentMismatch)));
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn test_verification_metrics() {
+        use crate::metrics;
+
+        let msgs = gen_messages().into_iter().take(3).collect::<Vec<_>>();
+        metrics::reset();
+        for msg in &msgs {
+            let mut container = DummyVec(small_vec![0xC0; 15]);
+            let proof = container.embed_commit(msg).unwrap();
+            container.verify(msg, &proof).unwrap();
+
+            let commitment = container.convolve_commit(&SUPPLEMENT, msg).unwrap().0;
+            ConvolveCommitProof::<_, DummyVec, _>::verify(&SUPPLEMENT, msg, &commitment).unwrap();
+        }
+        let snapshot = metrics::snapshot();
+        assert_eq!(snapshot.embed_verifications, msgs.len() as u64);
+        assert_eq!(snapshot.convolve_verifications, msgs.len() as u64);
+    }
+
+    #[test]
+    fn test_convolve_verify_batch() {
+        let container = DummyVec(small_vec![0xC0; 15]);
+        let msgs: [SmallVec<u8>; 3] = [small_vec![0x01], small_vec![0x02], small_vec![0x03]];
+        let commitments = msgs
+            .iter()
+            .map(|msg| container.convolve_commit(&SUPPLEMENT, msg).unwrap().0)
+            .collect::<Vec<_>>();
+        let proof = SUPPLEMENT;
+
+        let mut items = msgs
+            .iter()
+            .zip(&commitments)
+            .map(|(msg, commitment)| (&proof, msg, commitment))
+            .collect::<Vec<_>>();
+        assert_eq!(ConvolveCommitProof::<_, DummyVec, _>::verify_batch(&items), Ok(()));
+        assert_eq!(ConvolveCommitProof::<SmallVec<u8>, DummyVec, _>::verify_batch(&[]), Ok(()));
+
+        items[1].2 = &commitments[2];
+        assert_eq!(
+            ConvolveCommitProof::<_, DummyVec, _>::verify_batch(&items),
+            Err((1, ConvolveVerifyError::CommitmentMismatch))
+        );
+    }
+
+    #[test]
+    fn test_convolve_commit_empty() {
+        let container = DummyVec(small_vec![0xC0; 15]);
+        let empty = SmallVec::<u8>::new();
+        let zero = small_vec![0x00];
+
+        let (commitment, proof) = container.convolve_commit(&SUPPLEMENT, &empty).unwrap();
+        let (commitment_prime, _) = container.convolve_commit(&SUPPLEMENT, &empty).unwrap();
+        assert_eq!(commitment, commitment_prime);
+        assert_eq!(
+            ConvolveCommitProof::<_, DummyVec, _>::verify(&proof, &empty, &commitment),
+            Ok(())
+        );
+
+        let (zero_commitment, _) = container.convolve_commit(&SUPPLEMENT, &zero).unwrap();
+        assert_ne!(commitment, zero_commitment);
+        assert_eq!(
+            ConvolveCommitProof::<_, DummyVec, _>::verify(&proof, &zero, &commitment),
+            Err(ConvolveVerifyError::CommitmentMismatch)
+        );
+    }
+
     #[test]
     fn test_convolve_commit() {
         convolve_commit_verify_suite::<SmallVec<u8>, DummyVec>(
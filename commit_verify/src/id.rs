@@ -21,15 +21,24 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display, Formatter};
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::marker::PhantomData;
 
 use amplify::confinement::{Confined, TinyVec, U64 as U64MAX};
-use amplify::Bytes32;
+use amplify::{ByteArray, Bytes32};
 use sha2::Sha256;
-use strict_encoding::{Sizing, StreamWriter, StrictDumb, StrictEncode, StrictType};
+use strict_encoding::{
+    DecodeError, Sizing, StreamReader, StreamWriter, StrictDecode, StrictDumb, StrictEncode,
+    StrictReader, StrictType,
+};
 use strict_types::typesys::TypeFqn;
 
-use crate::{Conceal, DigestExt, MerkleHash, MerkleLeaves, LIB_NAME_COMMIT_VERIFY};
+use crate::mpc::ProtocolId;
+use crate::{
+    ct_eq, CommitmentProtocol, Conceal, DigestExt, MerkleHash, MerkleLeaves, TaggedProtocol,
+    LIB_NAME_COMMIT_VERIFY,
+};
 
 const COMMIT_MAX_LEN: usize = U64MAX;
 
@@ -221,6 +230,16 @@ pub trait CommitEncode {
     fn commit_encode(&self, e: &mut CommitEngine);
 }
 
+/// Binds commitment to the protocol `P` by committing to the [`ProtocolId`]
+/// constructed as a tagged hash of [`TaggedProtocol::TAG`].
+impl<P: TaggedProtocol> CommitEncode for PhantomData<P> {
+    type CommitmentId = StrictHash;
+
+    fn commit_encode(&self, e: &mut CommitEngine) {
+        e.commit_to_serialized(&ProtocolId::from(Sha256::from_tag(P::TAG).finish()));
+    }
+}
+
 #[derive(Getters, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct CommitLayout {
     idty: TypeFqn,
@@ -261,6 +280,63 @@ where T: CommitEncode + StrictDumb
     }
 }
 
+/// Treatment of a structure field by the commitment procedure.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum CommitFieldKind {
+    /// Strict-serialized field value is committed to.
+    Serialized,
+    /// Strict-serialized optional field value is committed to.
+    Option,
+    /// Strict-serialized confined list is committed to.
+    List,
+    /// Strict-serialized confined set is committed to.
+    Set,
+    /// Strict-serialized confined map is committed to.
+    Map,
+    /// [`StrictHash`] commitment id of the field value is committed to.
+    Hashed,
+    /// Merkle root of the field value is committed to.
+    Merklized,
+    /// Strict-serialized concealed field value is committed to.
+    Concealed,
+    /// The field is not committed to.
+    Skipped,
+}
+
+/// Description of a structure field participating in the commitment.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CommitField {
+    /// Name of the field; for tuple structures, the field index.
+    pub name: &'static str,
+    /// Treatment of the field by the commitment procedure.
+    pub kind: CommitFieldKind,
+}
+
+/// Named layout of structures committed field by field, which is implemented
+/// by `#[derive(CommitEncode)]` with `strategy = fields`.
+///
+/// Complements [`CommitmentLayout`] with the names of the fields, helping to
+/// diagnose mismatches between commitments produced by different
+/// implementations.
+pub trait CommitFields: CommitEncode {
+    /// All fields of the structure in the order of their declaration,
+    /// including skipped ones.
+    const COMMIT_FIELDS: &'static [CommitField];
+
+    /// Returns names of the fields which are committed to, in the commitment
+    /// order, each paired with its [`CommitStep`].
+    fn commit_field_steps() -> Vec<(&'static str, CommitStep)>
+    where Self: StrictDumb {
+        let steps = Self::strict_dumb().commit().into_layout();
+        Self::COMMIT_FIELDS
+            .iter()
+            .filter(|field| field.kind != CommitFieldKind::Skipped)
+            .map(|field| field.name)
+            .zip(steps)
+            .collect()
+    }
+}
+
 /// High-level API used in client-side validation for producing a single
 /// commitment to the data, which includes running all necessary procedures like
 /// concealment with [`crate::Conceal`], merklization, strict encoding,
@@ -286,10 +362,172 @@ impl<T: CommitEncode> CommitId for T {
         engine
     }
 
-    fn commit_id(&self) -> Self::CommitmentId { self.commit().finish().into() }
+    fn commit_id(&self) -> Self::CommitmentId {
+        #[cfg(feature = "metrics")]
+        crate::metrics::count_commitment();
+
+        self.commit().finish().into()
+    }
 }
 
-#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+#[derive(Debug)]
+struct HashingReader<R: io::Read> {
+    hasher: Sha256,
+    reader: R,
+}
+
+impl<R: io::Read> io::Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.hasher.input_raw(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Errors verifying commitment with [`StreamingVerifier`].
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum StreamVerifyError {
+    /// commitment doesn't match the message.
+    InvalidCommitment,
+
+    /// the stream has unconsumed data after the last decoded value.
+    UnconsumedData,
+
+    /// unable to read the stream.
+    ///
+    /// Details: {0}
+    #[from]
+    Io(io::Error),
+}
+
+/// Verifier decoding strict-encoded data from a reader while simultaneously
+/// accumulating commitment to the read bytes, such that the data can be both
+/// parsed and verified in a single pass.
+///
+/// The accumulated commitment matches [`CommitId::commit_id`] of the types
+/// committing to their strict serialization (see
+/// [`CommitEngine::commit_to_serialized`]).
+#[derive(Debug)]
+pub struct StreamingVerifier<Id: CommitmentId, R: io::Read> {
+    reader: StrictReader<StreamReader<HashingReader<R>>>,
+    _phantom: PhantomData<Id>,
+}
+
+impl<Id: CommitmentId, R: io::Read> StreamingVerifier<Id, R> {
+    /// Constructs verifier reading data from `reader` and committing to them
+    /// with [`CommitmentId::TAG`].
+    pub fn new(reader: R) -> Self {
+        let reader = HashingReader {
+            hasher: Sha256::from_tag(Id::TAG),
+            reader,
+        };
+        Self {
+            reader: StrictReader::with(StreamReader::new::<COMMIT_MAX_LEN>(reader)),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Decodes next value from the stream, adding its bytes to the
+    /// commitment.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the data can't be decoded as a value of type `T`.
+    pub fn read<T: StrictDecode>(&mut self) -> Result<T, DecodeError> {
+        T::strict_decode(&mut self.reader)
+    }
+
+    /// Completes reading of the stream and verifies the accumulated commitment
+    /// against the `expected` one.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`StreamVerifyError::UnconsumedData`] if the stream has
+    /// unconsumed data, with [`StreamVerifyError::Io`] if it can't be read,
+    /// and with [`StreamVerifyError::InvalidCommitment`] if the commitment
+    /// doesn't match.
+    pub fn verify(self, expected: Id) -> Result<(), StreamVerifyError> {
+        let mut reader = self.reader.unbox().unconfine();
+        let mut buf = [0u8; 1];
+        loop {
+            match io::Read::read(&mut reader.reader, &mut buf) {
+                Ok(0) => break,
+                Ok(_) => return Err(StreamVerifyError::UnconsumedData),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        if Id::from(reader.hasher) != expected {
+            return Err(StreamVerifyError::InvalidCommitment);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct HashingWriter<W: io::Write> {
+    hasher: Sha256,
+    writer: W,
+}
+
+impl<W: io::Write> io::Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.writer.write(buf)?;
+        self.hasher.input_raw(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> { self.writer.flush() }
+}
+
+/// Writer strict-encoding data into an inner writer while simultaneously
+/// accumulating commitment to the written bytes, such that the data can be
+/// both serialized and committed to in a single pass.
+///
+/// The accumulated commitment matches [`CommitId::commit_id`] of the types
+/// committing to their strict serialization (see
+/// [`CommitEngine::commit_to_serialized`]).
+#[derive(Debug)]
+pub struct TeeWriter<Id: CommitmentId, W: io::Write> {
+    writer: StreamWriter<HashingWriter<W>>,
+    _phantom: PhantomData<Id>,
+}
+
+impl<Id: CommitmentId, W: io::Write> TeeWriter<Id, W> {
+    /// Constructs writer passing data to the `writer` and committing to them
+    /// with [`CommitmentId::TAG`].
+    pub fn new(writer: W) -> Self {
+        let writer = HashingWriter {
+            hasher: Sha256::from_tag(Id::TAG),
+            writer,
+        };
+        Self {
+            writer: StreamWriter::new::<COMMIT_MAX_LEN>(writer),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Strict-encodes the value into the inner writer, adding its bytes to
+    /// the commitment.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the inner writer fails or the data written since the
+    /// construction exceed the commitment size limit.
+    pub fn write<T: StrictEncode>(&mut self, value: &T) -> io::Result<()> {
+        value.strict_write(&mut self.writer)
+    }
+
+    /// Completes writing, returning the inner writer and the accumulated
+    /// commitment.
+    pub fn into_inner(self) -> (W, Id) {
+        let writer = self.writer.unconfine();
+        (writer.writer, Id::from(writer.hasher))
+    }
+}
+
+#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Debug, From)]
 #[wrapper(Deref, BorrowSlice, Display, FromStr, Hex, Index, RangeOps)]
 #[derive(StrictDumb, strict_encoding::StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
@@ -308,6 +546,320 @@ impl CommitmentId for StrictHash {
     const TAG: &'static str = "urn:ubideco:strict-types:value-hash#2024-02-10";
 }
 
+impl PartialEq for StrictHash {
+    /// Compares the hashes in constant time (see [`crate::ct_eq`]).
+    fn eq(&self, other: &Self) -> bool { ct_eq(self.as_slice(), other.as_slice()) }
+}
+
+impl Eq for StrictHash {}
+
+impl Hash for StrictHash {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+}
+
 impl From<Sha256> for StrictHash {
     fn from(hash: Sha256) -> Self { hash.finish().into() }
 }
+
+/// Commitment protocol with a human-readable identifier, which prefixes
+/// string representations of the commitment ids of the protocol (see
+/// [`QualifiedId`]).
+pub trait HriProtocol: CommitmentProtocol {
+    /// Human-readable identifier of the protocol (up to 16 characters), like
+    /// `rgb` or `lnpbp4`.
+    const HRI: &'static str;
+}
+
+/// Commitment id bound to the protocol `P`.
+///
+/// The id is displayed as a Baid64 string prefixed with the protocol
+/// [`HriProtocol::HRI`], and the prefix is checked during parsing, such that
+/// an id of one protocol can't be used where an id of other protocol is
+/// expected.
+pub struct QualifiedId<P: HriProtocol>(Bytes32, PhantomData<P>);
+
+impl<P: HriProtocol> QualifiedId<P> {
+    /// Binds commitment id to the protocol `P`.
+    pub fn with(id: impl ByteArray<32>) -> Self { Self::from(id.to_byte_array()) }
+
+    /// Returns commitment id bytes.
+    pub fn to_byte_array(&self) -> [u8; 32] { self.0.to_byte_array() }
+}
+
+impl<P: HriProtocol> From<[u8; 32]> for QualifiedId<P> {
+    fn from(id: [u8; 32]) -> Self { Self(Bytes32::from_byte_array(id), PhantomData) }
+}
+
+mod _qualified_id {
+    use std::cmp::Ordering;
+    use std::fmt::Debug;
+    use std::str::FromStr;
+
+    use baid64::{Baid64ParseError, DisplayBaid64, FromBaid64Str};
+
+    use super::*;
+
+    impl<P: HriProtocol> Copy for QualifiedId<P> {}
+
+    impl<P: HriProtocol> Clone for QualifiedId<P> {
+        fn clone(&self) -> Self { *self }
+    }
+
+    impl<P: HriProtocol> PartialEq for QualifiedId<P> {
+        fn eq(&self, other: &Self) -> bool { crate::ct_eq(self.0.as_slice(), other.0.as_slice()) }
+    }
+
+    impl<P: HriProtocol> Eq for QualifiedId<P> {}
+
+    impl<P: HriProtocol> PartialOrd for QualifiedId<P> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+    }
+
+    impl<P: HriProtocol> Ord for QualifiedId<P> {
+        fn cmp(&self, other: &Self) -> Ordering { self.0.cmp(&other.0) }
+    }
+
+    impl<P: HriProtocol> Hash for QualifiedId<P> {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) { self.0.hash(state) }
+    }
+
+    impl<P: HriProtocol> Debug for QualifiedId<P> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.debug_tuple("QualifiedId")
+                .field(&P::HRI)
+                .field(&self.0)
+                .finish()
+        }
+    }
+
+    impl<P: HriProtocol> DisplayBaid64 for QualifiedId<P> {
+        const HRI: &'static str = P::HRI;
+        const CHUNKING: bool = true;
+        const PREFIX: bool = true;
+        const EMBED_CHECKSUM: bool = false;
+        const MNEMONIC: bool = false;
+
+        fn to_baid64_payload(&self) -> [u8; 32] { self.to_byte_array() }
+    }
+
+    impl<P: HriProtocol> FromBaid64Str for QualifiedId<P> {}
+
+    impl<P: HriProtocol> Display for QualifiedId<P> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.fmt_baid64(f) }
+    }
+
+    impl<P: HriProtocol> FromStr for QualifiedId<P> {
+        type Err = Baid64ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            // Unlike the generic Baid64 parser, we require the protocol prefix to be
+            // present
+            match s.rsplit_once(':') {
+                Some((hri, _)) if hri == P::HRI => Self::from_baid64_str(s),
+                _ => Err(Baid64ParseError::InvalidHri(s.to_owned(), P::HRI)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use strict_encoding::StrictWriter;
+
+    use super::*;
+    use crate::mpc::Leaf;
+    use crate::MerkleNode;
+
+    #[test]
+    fn tee_writer() {
+        let node = MerkleNode::branches(0u8, 2u32, MerkleHash::from([1u8; 32]), [2u8; 32].into());
+        let mut writer = TeeWriter::<MerkleHash, _>::new(vec![]);
+        writer.write(&node).unwrap();
+        let (data, id) = writer.into_inner();
+        let serialized = node
+            .strict_encode(StrictWriter::in_memory::<{ usize::MAX }>())
+            .unwrap()
+            .unbox()
+            .unconfine();
+        assert_eq!(data, serialized);
+        assert_eq!(id, node.commit_id());
+
+        let values = (0..0x1000u64).collect::<Vec<_>>();
+        let mut writer = TeeWriter::<StrictHash, _>::new(vec![]);
+        for value in &values {
+            writer.write(value).unwrap();
+        }
+        let (data, id) = writer.into_inner();
+        let mut verifier = StreamingVerifier::<StrictHash, _>::new(data.as_slice());
+        for value in &values {
+            assert_eq!(verifier.read::<u64>().unwrap(), *value);
+        }
+        verifier.verify(id).unwrap();
+    }
+
+    #[test]
+    fn streaming_verifier() {
+        let values = (0..0x20000u64).collect::<Vec<_>>();
+        let data = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(data.len(), 1024 * 1024);
+        let mut hasher = Sha256::from_tag(StrictHash::TAG);
+        hasher.input_raw(&data);
+        let expected = StrictHash::from(hasher);
+
+        let mut verifier = StreamingVerifier::<StrictHash, _>::new(data.as_slice());
+        for value in &values {
+            assert_eq!(verifier.read::<u64>().unwrap(), *value);
+        }
+        verifier.verify(expected).unwrap();
+
+        let mut verifier = StreamingVerifier::<StrictHash, _>::new(data.as_slice());
+        for _ in &values {
+            verifier.read::<u64>().unwrap();
+        }
+        assert!(matches!(
+            verifier.verify(StrictHash::from([0u8; 32])),
+            Err(StreamVerifyError::InvalidCommitment)
+        ));
+
+        let mut verifier = StreamingVerifier::<StrictHash, _>::new(data.as_slice());
+        verifier.read::<u64>().unwrap();
+        assert!(matches!(verifier.verify(expected), Err(StreamVerifyError::UnconsumedData)));
+    }
+
+    /// Reader failing with the given error once all the data are read.
+    struct FailingReader<'a> {
+        data: &'a [u8],
+        error: Option<io::ErrorKind>,
+    }
+
+    impl io::Read for FailingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() {
+                if let Some(kind) = self.error.take() {
+                    return Err(kind.into());
+                }
+            }
+            self.data.read(buf)
+        }
+    }
+
+    #[test]
+    fn streaming_verifier_io_errors() {
+        let data = 0xdeadcafeu64.to_le_bytes();
+        let mut hasher = Sha256::from_tag(StrictHash::TAG);
+        hasher.input_raw(&data);
+        let expected = StrictHash::from(hasher);
+
+        let reader = FailingReader {
+            data: &data,
+            error: Some(io::ErrorKind::Interrupted),
+        };
+        let mut verifier = StreamingVerifier::<StrictHash, _>::new(reader);
+        assert_eq!(verifier.read::<u64>().unwrap(), 0xdeadcafe);
+        verifier.verify(expected).unwrap();
+
+        let reader = FailingReader {
+            data: &data,
+            error: Some(io::ErrorKind::BrokenPipe),
+        };
+        let mut verifier = StreamingVerifier::<StrictHash, _>::new(reader);
+        assert_eq!(verifier.read::<u64>().unwrap(), 0xdeadcafe);
+        assert!(matches!(
+            verifier.verify(expected),
+            Err(StreamVerifyError::Io(err)) if err.kind() == io::ErrorKind::BrokenPipe
+        ));
+    }
+
+    #[test]
+    fn streaming_verifier_commit_id() {
+        let leaf = Leaf::entropy(0xdeadcafe, 7);
+        let data = leaf
+            .strict_encode(StrictWriter::in_memory::<64>())
+            .unwrap()
+            .unbox()
+            .unconfine();
+        let mut verifier = StreamingVerifier::<MerkleHash, _>::new(data.as_slice());
+        assert_eq!(verifier.read::<Leaf>().unwrap(), leaf);
+        verifier.verify(leaf.commit_id()).unwrap();
+    }
+
+    #[test]
+    fn qualified_id() {
+        enum Rgb {}
+        impl CommitmentProtocol for Rgb {}
+        impl HriProtocol for Rgb {
+            const HRI: &'static str = "rgb";
+        }
+
+        enum Lnpbp4 {}
+        impl CommitmentProtocol for Lnpbp4 {}
+        impl HriProtocol for Lnpbp4 {
+            const HRI: &'static str = "lnpbp4";
+        }
+
+        let id = QualifiedId::<Rgb>::with(StrictHash::from([0xA5; 32]));
+        let s = id.to_string();
+        assert!(s.starts_with("rgb:"));
+        assert_eq!(QualifiedId::<Rgb>::from_str(&s).unwrap(), id);
+        assert_eq!(QualifiedId::<Rgb>::from_str(&s).unwrap().to_byte_array(), [0xA5; 32]);
+        assert!(QualifiedId::<Lnpbp4>::from_str(&s).is_err());
+        assert!(QualifiedId::<Rgb>::from_str(s.trim_start_matches("rgb:")).is_err());
+
+        let other = QualifiedId::<Lnpbp4>::from([0xA5; 32]).to_string();
+        assert!(other.starts_with("lnpbp4:"));
+        assert!(QualifiedId::<Rgb>::from_str(&other).is_err());
+    }
+
+    #[test]
+    fn phantom_protocol() {
+        enum ProtoA {}
+        impl CommitmentProtocol for ProtoA {}
+        impl TaggedProtocol for ProtoA {
+            const TAG: &'static str = "urn:ubideco:test:proto-a";
+        }
+
+        enum ProtoB {}
+        impl CommitmentProtocol for ProtoB {}
+        impl TaggedProtocol for ProtoB {
+            const TAG: &'static str = "urn:ubideco:test:proto-b";
+        }
+
+        struct Data<P: TaggedProtocol> {
+            value: u64,
+            protocol: PhantomData<P>,
+        }
+
+        impl<P: TaggedProtocol> CommitEncode for Data<P> {
+            type CommitmentId = StrictHash;
+
+            fn commit_encode(&self, e: &mut CommitEngine) {
+                self.protocol.commit_encode(e);
+                e.commit_to_serialized(&self.value);
+            }
+        }
+
+        let a = Data::<ProtoA> {
+            value: 42,
+            protocol: PhantomData,
+        };
+        let b = Data::<ProtoB> {
+            value: 42,
+            protocol: PhantomData,
+        };
+        assert_ne!(a.commit_id(), b.commit_id());
+        assert_ne!(PhantomData::<ProtoA>.commit_id(), PhantomData::<ProtoB>.commit_id());
+        assert_eq!(
+            a.commit_id(),
+            Data::<ProtoA> {
+                value: 42,
+                protocol: PhantomData
+            }
+            .commit_id()
+        );
+    }
+}
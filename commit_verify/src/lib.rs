@@ -43,13 +43,15 @@ extern crate serde_crate as serde;
 extern crate core;
 
 #[cfg(feature = "derive")]
-pub use commit_encoding_derive::CommitEncode;
+pub use commit_encoding_derive::{CommitEncode, Conceal};
 
 mod commit;
 mod conceal;
 mod convolve;
 mod embed;
 mod id;
+mod reconcile;
+mod threshold;
 #[cfg(feature = "stl")]
 pub mod stl;
 
@@ -57,17 +59,38 @@ pub mod merkle;
 pub mod mpc;
 mod digest;
 pub mod vesper;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(test)]
+mod test_vectors;
 
-pub use commit::{CommitVerify, TryCommitVerify, VerifyError};
+pub use commit::{
+    commit_sequence, CommitVerify, PairProtocol, SequenceProtocol, TryCommitVerify, VerifyError,
+};
 pub use conceal::Conceal;
-pub use convolve::{ConvolveCommit, ConvolveCommitProof, ConvolveVerifyError};
-pub use digest::{Digest, DigestExt, Ripemd160, Sha256};
+pub use convolve::{
+    AggregateProof, ConvolveCommit, ConvolveCommitHint, ConvolveCommitProof, ConvolveVerifyError,
+    SupplementSource,
+};
+#[cfg(feature = "bitcoin_hashes")]
+pub use digest::CommitmentTag;
+pub use digest::{
+    ct_eq, subtag, Digest, DigestExt, LenPrefixError, Ripemd160, SectionError, Sha256, Sha512_256,
+    SubtagError, TaggedCommitBuilder, TaggedEngine, SUBTAG_SEPARATOR,
+};
 pub use embed::{EmbedCommitProof, EmbedCommitVerify, EmbedVerifyError, VerifyEq};
 pub use id::{
-    CommitColType, CommitEncode, CommitEngine, CommitId, CommitLayout, CommitStep, CommitmentId,
-    CommitmentLayout, StrictHash,
+    CommitColType, CommitEncode, CommitEngine, CommitField, CommitFieldKind, CommitFields,
+    CommitId, CommitLayout, CommitStep, CommitmentId, CommitmentLayout, HriProtocol, QualifiedId,
+    StreamVerifyError, StreamingVerifier, StrictHash, TeeWriter,
 };
-pub use merkle::{MerkleBuoy, MerkleHash, MerkleLeaves, MerkleNode, NodeBranching};
+pub use merkle::{
+    CachedMerkleSource, MerkleBuoy, MerkleHash, MerkleLeaves, MerkleNode, MerklePath,
+    MerklePathError, MerkleRoot, MerkleShape, MerkleShapeHash, NodeBranching, OddNode, SuperProof,
+    SuperTree, SuperTreeError, ANNOTATED_LEAF_TAG,
+};
+pub use reconcile::{ReconcilableSetCommitment, ReconcileCell, ReconcileError, SetDifference};
+pub use threshold::{share_commitment, ThresholdCommitment, ThresholdError};
 
 pub const LIB_NAME_COMMIT_VERIFY: &str = "CommitVerify";
 
@@ -83,6 +106,17 @@ pub const LIB_NAME_COMMIT_VERIFY: &str = "CommitVerify";
 ///   its own `Proof` type defined as an associated generic).
 pub trait CommitmentProtocol {}
 
+/// Commitment protocol having a unique tag, which can be used to bind
+/// commitments to the protocol.
+///
+/// Commitment encoding of `PhantomData<P: TaggedProtocol>` includes the
+/// protocol tag, such that the same data bound to different protocols via a
+/// phantom marker produce distinct commitments.
+pub trait TaggedProtocol: CommitmentProtocol {
+    /// Unique protocol tag, usually in form of URN.
+    const TAG: &'static str;
+}
+
 /// Protocol defining commits created by using externally created hash value
 /// *optionally pre-tagged*.
 pub struct UntaggedProtocol;
@@ -19,18 +19,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use core::cell::Cell;
 use core::{iter, slice};
-use std::collections::{btree_set, BTreeSet};
+use std::collections::{btree_set, BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
 use std::ops::SubAssign;
 
-use amplify::confinement::Confined;
+use amplify::confinement::{Confined, TinyVec};
 use amplify::num::{u256, u5};
 use amplify::{Bytes32, Wrapper};
 use sha2::Sha256;
-use strict_encoding::StrictEncode;
+use strict_encoding::{StrictDeserialize, StrictEncode, StrictSerialize};
 
-use crate::digest::DigestExt;
-use crate::{CommitId, CommitmentId, LIB_NAME_COMMIT_VERIFY};
+use crate::digest::{ct_eq, DigestExt, SUBTAG_SEPARATOR};
+use crate::{CommitEngine, CommitId, CommitmentId, CommitmentProtocol, LIB_NAME_COMMIT_VERIFY};
 
 /// Type of merkle node branching.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -51,6 +54,28 @@ pub enum NodeBranching {
     Branch = 0x02,
 }
 
+/// Strategy for processing a node which doesn't have a sibling, i.e. a node
+/// at the lowest level of a merkle branch of an odd width.
+///
+/// Since the tree is split into branches of (almost) equal width, at each
+/// level such node appears only at the bottom of the branch, thus the strategy
+/// is consistently applied to all of the tree levels.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum OddNode {
+    /// Commit to the node with [`NodeBranching::Single`] branching, as defined
+    /// by [LNPBP-81] standard.
+    ///
+    /// [LNPBP-81]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0081.md
+    #[default]
+    Single,
+
+    /// Commit to the node paired with its own copy.
+    Duplicate,
+
+    /// Commit to the node paired with a node consisting of zero bytes.
+    ZeroPad,
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
@@ -103,7 +128,7 @@ impl MerkleNode {
 /// procedure.
 ///
 /// [LNPBP-4]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0004.md
-#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Debug, From)]
 #[wrapper(Deref, BorrowSlice, Display, FromStr, Hex, Index, RangeOps)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
@@ -122,11 +147,28 @@ impl CommitmentId for MerkleHash {
     const TAG: &'static str = "urn:ubideco:merkle:node#2024-01-31";
 }
 
+impl PartialEq for MerkleHash {
+    /// Compares the hashes in constant time (see [`crate::ct_eq`]).
+    fn eq(&self, other: &Self) -> bool { ct_eq(self.as_slice(), other.as_slice()) }
+}
+
+impl Eq for MerkleHash {}
+
+impl Hash for MerkleHash {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+}
+
 impl From<Sha256> for MerkleHash {
     fn from(hash: Sha256) -> Self { hash.finish().into() }
 }
 
+/// Tag of the annotated leaf nodes (see [`MerkleHash::annotated_leaf`]), which
+/// is `annotated-leaf` sub-tag of the [`MerkleHash::TAG`] (see
+/// [`crate::subtag`]).
+pub const ANNOTATED_LEAF_TAG: &str = "urn:ubideco:merkle:node#2024-01-31/annotated-leaf";
+
 const VIRTUAL_LEAF: MerkleHash = MerkleHash(Bytes32::from_array([0xFF; 32]));
+const ZERO_LEAF: MerkleHash = MerkleHash(Bytes32::from_array([0x00; 32]));
 
 impl MerkleHash {
     pub fn void(depth: impl Into<u8>, width: impl Into<u256>) -> Self {
@@ -151,16 +193,132 @@ impl MerkleHash {
     /// Merklization procedure that uses tagged hashes with depth commitments
     /// according to [LNPBP-81] standard of client-side-validation merklization.
     ///
+    /// An empty set of leaves is a valid input, which root is defined as
+    /// [`MerkleHash::void`] node of zero depth and zero width. Since the root
+    /// of a single-leaf tree is the leaf node itself (which is a tagged hash
+    /// of the leaf), it does not match the empty-set root unless the leaf is
+    /// the void node itself; use [`MerkleHash::merklize_shaped`] to
+    /// distinguish such cases.
+    ///
     /// [LNPBP-81]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0081.md
     pub fn merklize(leaves: &impl MerkleLeaves) -> Self {
-        let mut nodes = leaves.merkle_leaves().map(|leaf| leaf.commit_id());
+        Self::merklize_with(leaves, OddNode::default())
+    }
+
+    /// Merklization procedure working like [`MerkleHash::merklize`], but using
+    /// custom [`OddNode`] strategy for the nodes without a sibling.
+    ///
+    /// Trees of a width equal to a power of two do not have such nodes, thus
+    /// their roots do not depend on the strategy. [`MerklePath`] inclusion
+    /// proofs are supported only for the [`OddNode::Single`] strategy.
+    pub fn merklize_with(leaves: &impl MerkleLeaves, odd: OddNode) -> Self {
+        Self::merklize_nodes(leaves.merkle_leaves().map(|leaf| leaf.commit_id()), odd)
+    }
+
+    /// Merklization procedure working like [`MerkleHash::merklize`], but taking
+    /// already computed leaf nodes (i.e. the [`CommitId::commit_id`] of each
+    /// of the leaves) instead of the leaves themselves.
+    ///
+    /// Allows to compute leaf nodes separately from the tree, for instance in
+    /// a different process.
+    pub fn from_leaf_hashes(leaves: impl IntoIterator<Item = MerkleHash>) -> Self {
+        let leaves = leaves.into_iter().collect::<Vec<_>>();
+        Self::merklize_nodes(leaves.into_iter(), OddNode::default())
+    }
+
+    /// Merklization procedure which, in addition to the root, commits to the
+    /// shape of the tree (see [`MerkleShape`]).
+    pub fn merklize_shaped(leaves: &impl MerkleLeaves) -> MerkleShape {
+        let width = u32::try_from(leaves.merkle_leaves().len())
+            .expect("too many merkle leaves (more than 2^31)");
+        MerkleShape {
+            root: Self::merklize(leaves),
+            width,
+            depth: width.next_power_of_two().trailing_zeros() as u8,
+        }
+    }
+
+    /// Merklization of leaves annotated with some additional data (like
+    /// weights or amounts), such that the tree commits to both the leaf and
+    /// its annotation. Each leaf node is computed with
+    /// [`MerkleHash::annotated_leaf`].
+    pub fn merklize_annotated<L: StrictEncode, A: StrictEncode>(leaves: &[(L, A)]) -> Self {
+        Self::merklize_nodes(
+            leaves
+                .iter()
+                .map(|(leaf, annotation)| Self::annotated_leaf(leaf, annotation)),
+            OddNode::Single,
+        )
+    }
+
+    /// Computes leaf node as a hash of strict-serialized annotation followed by
+    /// the strict-serialized leaf, tagged with [`ANNOTATED_LEAF_TAG`] to
+    /// distinguish annotated leaves from the tree branch nodes.
+    pub fn annotated_leaf<L: StrictEncode, A: StrictEncode>(leaf: &L, annotation: &A) -> Self {
+        let mut engine = CommitEngine::new(ANNOTATED_LEAF_TAG);
+        engine.commit_to_serialized(annotation);
+        engine.commit_to_serialized(leaf);
+        engine.set_finished();
+        engine.finish().into()
+    }
+
+    /// Parallel version of [`MerkleHash::merklize`], producing the same root
+    /// while distributing computation of the leaves and tree branches across
+    /// threads.
+    #[cfg(feature = "rayon")]
+    pub fn merklize_parallel<T: MerkleLeaves>(leaves: &T) -> Self
+    where T::Leaf: Send {
+        use rayon::prelude::*;
+
+        let nodes = leaves
+            .merkle_leaves()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|leaf| leaf.commit_id())
+            .collect::<Vec<_>>();
+        let base_width =
+            u32::try_from(nodes.len()).expect("too many merkle leaves (more than 2^31)");
+        if base_width == 1 {
+            // If we have just one leaf, it's MerkleNode value is the root
+            nodes[0]
+        } else {
+            Self::_merklize_parallel(&nodes, u5::ZERO, base_width)
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    fn _merklize_parallel(nodes: &[MerkleHash], depth: u5, base_width: u32) -> Self {
+        /// Branches below this width are computed sequentially, since the
+        /// threading overhead exceeds the gain.
+        const SEQUENTIAL_WIDTH: usize = 64;
+
+        let branch_width = nodes.len() as u32;
+        if nodes.len() <= SEQUENTIAL_WIDTH {
+            return Self::_merklize(
+                nodes.iter().copied(),
+                depth,
+                branch_width,
+                base_width,
+                OddNode::Single,
+            );
+        }
+        let div = branch_width / 2 + branch_width % 2;
+        let (left, right) = nodes.split_at(div as usize);
+        let (branch1, branch2) = rayon::join(
+            || Self::_merklize_parallel(left, depth + 1, base_width),
+            || Self::_merklize_parallel(right, depth + 1, base_width),
+        );
+        MerkleHash::branches(depth, base_width, branch1, branch2)
+    }
+
+    fn merklize_nodes(mut nodes: impl ExactSizeIterator<Item = MerkleHash>, odd: OddNode) -> Self {
         let base_width =
             u32::try_from(nodes.len()).expect("too many merkle leaves (more than 2^31)");
         if base_width == 1 {
             // If we have just one leaf, it's MerkleNode value is the root
             nodes.next().expect("length is 1")
         } else {
-            Self::_merklize(nodes, u5::ZERO, base_width, base_width)
+            Self::_merklize(nodes, u5::ZERO, base_width, base_width, odd)
         }
     }
 
@@ -169,13 +327,18 @@ impl MerkleHash {
         depth: u5,
         branch_width: u32,
         base_width: u32,
+        odd: OddNode,
     ) -> Self {
         if branch_width <= 2 {
             match (iter.next(), iter.next()) {
                 (None, None) => MerkleHash::void(depth, base_width),
                 // Here, a single node means Merkle tree width non-equal to the power of 2, thus we
                 // need to process it with a special encoding.
-                (Some(branch), None) => MerkleHash::single(depth, base_width, branch),
+                (Some(branch), None) => match odd {
+                    OddNode::Single => MerkleHash::single(depth, base_width, branch),
+                    OddNode::Duplicate => MerkleHash::branches(depth, base_width, branch, branch),
+                    OddNode::ZeroPad => MerkleHash::branches(depth, base_width, branch, ZERO_LEAF),
+                },
                 (Some(branch1), Some(branch2)) => {
                     MerkleHash::branches(depth, base_width, branch1, branch2)
                 }
@@ -192,20 +355,554 @@ impl MerkleHash {
                 // TODO: Do this without allocation
                 .collect::<Vec<_>>()
                 .into_iter();
-            let branch1 = Self::_merklize(slice, depth + 1, div, base_width);
-            let branch2 = Self::_merklize(iter, depth + 1, branch_width - div, base_width);
+            let branch1 = Self::_merklize(slice, depth + 1, div, base_width, odd);
+            let branch2 = Self::_merklize(iter, depth + 1, branch_width - div, base_width, odd);
 
             MerkleHash::branches(depth, base_width, branch1, branch2)
         }
     }
 }
 
+/// Merkle tree root bound to the shape of the tree.
+///
+/// Trees of different shape may have the same root: for instance, the root of
+/// a single-leaf tree is the leaf itself, which may be a [`MerkleNode`] of some
+/// other tree. Commitment to [`MerkleShape`] distinguishes such trees.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[derive(CommitEncode)]
+#[commit_encode(crate = crate, strategy = strict, id = MerkleShapeHash)]
+pub struct MerkleShape {
+    pub root: MerkleHash,
+    /// Number of leaves in the tree.
+    pub width: u32,
+    /// Depth of the tree leaves.
+    pub depth: u8,
+}
+
+/// Commitment to the merkle tree root and its shape, produced from
+/// [`MerkleShape`].
+#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Debug, From)]
+#[wrapper(Deref, BorrowSlice, Display, FromStr, Hex, Index, RangeOps)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", transparent)
+)]
+pub struct MerkleShapeHash(
+    #[from]
+    #[from([u8; 32])]
+    Bytes32,
+);
+
+impl CommitmentId for MerkleShapeHash {
+    const TAG: &'static str = "urn:ubideco:merkle:shape#2026-10-14";
+}
+
+impl PartialEq for MerkleShapeHash {
+    /// Compares the hashes in constant time (see [`crate::ct_eq`]).
+    fn eq(&self, other: &Self) -> bool { ct_eq(self.as_slice(), other.as_slice()) }
+}
+
+impl Eq for MerkleShapeHash {}
+
+impl Hash for MerkleShapeHash {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+}
+
+impl From<Sha256> for MerkleShapeHash {
+    fn from(hash: Sha256) -> Self { hash.finish().into() }
+}
+
+/// Merkle tree root produced by [`MerkleHash::merklize`] and bound to a
+/// specific commitment protocol `P`, such that roots from different protocols
+/// can't be mixed up or compared.
+///
+/// ```compile_fail
+/// # use commit_verify::{CommitmentProtocol, MerkleHash, MerkleRoot};
+/// enum ProtoA {}
+/// impl CommitmentProtocol for ProtoA {}
+/// enum ProtoB {}
+/// impl CommitmentProtocol for ProtoB {}
+///
+/// let a = MerkleRoot::<ProtoA>::from(MerkleHash::from([0u8; 32]));
+/// let b = MerkleRoot::<ProtoB>::from(MerkleHash::from([0u8; 32]));
+/// assert_eq!(a, b);
+/// ```
+pub struct MerkleRoot<P: CommitmentProtocol>(MerkleHash, PhantomData<P>);
+
+impl<P: CommitmentProtocol> MerkleRoot<P> {
+    /// Computes merkle root with [`MerkleHash::merklize`].
+    pub fn merklize(leaves: &impl MerkleLeaves) -> Self { MerkleHash::merklize(leaves).into() }
+
+    /// Computes merkle root with [`MerkleHash::merklize_annotated`].
+    pub fn merklize_annotated<L: StrictEncode, A: StrictEncode>(leaves: &[(L, A)]) -> Self {
+        MerkleHash::merklize_annotated(leaves).into()
+    }
+
+    /// Returns merkle root without the protocol binding.
+    pub const fn merkle_hash(&self) -> MerkleHash { self.0 }
+}
+
+impl<P: CommitmentProtocol> From<MerkleHash> for MerkleRoot<P> {
+    fn from(hash: MerkleHash) -> Self { Self(hash, PhantomData) }
+}
+
+impl<P: CommitmentProtocol> From<MerkleRoot<P>> for MerkleHash {
+    fn from(root: MerkleRoot<P>) -> Self { root.0 }
+}
+
+mod _merkle_root {
+    use std::cmp::Ordering;
+    use std::fmt::{self, Debug, Display, Formatter};
+    use std::hash::{Hash, Hasher};
+    use std::io;
+    use std::str::FromStr;
+
+    use amplify::hex;
+    use strict_encoding::{
+        DecodeError, ReadTuple, StrictDecode, StrictDeserialize, StrictDumb, StrictEncode,
+        StrictProduct, StrictSerialize, StrictTuple, StrictType, TypeName, TypedRead, TypedWrite,
+    };
+
+    use super::*;
+
+    impl<P: CommitmentProtocol> Copy for MerkleRoot<P> {}
+
+    impl<P: CommitmentProtocol> Clone for MerkleRoot<P> {
+        fn clone(&self) -> Self { *self }
+    }
+
+    impl<P: CommitmentProtocol> PartialEq for MerkleRoot<P> {
+        fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
+    }
+
+    impl<P: CommitmentProtocol> Eq for MerkleRoot<P> {}
+
+    impl<P: CommitmentProtocol> PartialOrd for MerkleRoot<P> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+    }
+
+    impl<P: CommitmentProtocol> Ord for MerkleRoot<P> {
+        fn cmp(&self, other: &Self) -> Ordering { self.0.cmp(&other.0) }
+    }
+
+    impl<P: CommitmentProtocol> Hash for MerkleRoot<P> {
+        fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+    }
+
+    impl<P: CommitmentProtocol> Debug for MerkleRoot<P> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.debug_tuple("MerkleRoot").field(&self.0).finish()
+        }
+    }
+
+    impl<P: CommitmentProtocol> Display for MerkleRoot<P> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { Display::fmt(&self.0, f) }
+    }
+
+    impl<P: CommitmentProtocol> FromStr for MerkleRoot<P> {
+        type Err = hex::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> { MerkleHash::from_str(s).map(Self::from) }
+    }
+
+    impl<P: CommitmentProtocol> StrictDumb for MerkleRoot<P> {
+        fn strict_dumb() -> Self { MerkleHash::strict_dumb().into() }
+    }
+
+    impl<P: CommitmentProtocol> StrictType for MerkleRoot<P> {
+        const STRICT_LIB_NAME: &'static str = LIB_NAME_COMMIT_VERIFY;
+        fn strict_name() -> Option<TypeName> { Some(tn!("MerkleRoot")) }
+    }
+
+    impl<P: CommitmentProtocol> StrictProduct for MerkleRoot<P> {}
+
+    impl<P: CommitmentProtocol> StrictTuple for MerkleRoot<P> {
+        const FIELD_COUNT: u8 = 1;
+    }
+
+    impl<P: CommitmentProtocol> StrictEncode for MerkleRoot<P> {
+        fn strict_encode<W: TypedWrite>(&self, writer: W) -> io::Result<W> {
+            writer.write_newtype::<Self>(&self.0)
+        }
+    }
+
+    impl<P: CommitmentProtocol> StrictDecode for MerkleRoot<P> {
+        fn strict_decode(reader: &mut impl TypedRead) -> Result<Self, DecodeError> {
+            reader.read_tuple(|r| r.read_field::<MerkleHash>().map(Self::from))
+        }
+    }
+
+    impl<P: CommitmentProtocol> StrictSerialize for MerkleRoot<P> {}
+    impl<P: CommitmentProtocol> StrictDeserialize for MerkleRoot<P> {}
+
+    #[cfg(feature = "serde")]
+    mod _serde {
+        use serde_crate::{Deserialize, Deserializer, Serialize, Serializer};
+
+        use super::*;
+
+        impl<P: CommitmentProtocol> Serialize for MerkleRoot<P> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where S: Serializer {
+                self.0.serialize(serializer)
+            }
+        }
+
+        impl<'de, P: CommitmentProtocol> Deserialize<'de> for MerkleRoot<P> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where D: Deserializer<'de> {
+                MerkleHash::deserialize(deserializer).map(Self::from)
+            }
+        }
+    }
+}
+
+/// Errors convolving [`MerklePath`] into the tree root, which happen when the
+/// path is malformed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum MerklePathError {
+    /// leaf position {pos} is outside of the tree of width {width}.
+    InvalidPosition { pos: u32, width: u32 },
+
+    /// merkle path is shorter than the depth of the tree.
+    PathTooShort,
+
+    /// merkle path is longer than the depth of the tree.
+    PathTooLong,
+}
+
+/// Inclusion proof for a leaf of a tree produced by the [LNPBP-81]
+/// merklization procedure.
+///
+/// The proof may be received from an untrusted party; malformed proofs fail
+/// [`MerklePath::convolve`] with [`MerklePathError`].
+///
+/// [LNPBP-81]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0081.md
+#[derive(Getters, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct MerklePath {
+    /// Position of the leaf in the tree.
+    #[getter(as_copy)]
+    pos: u32,
+
+    /// Number of leaves in the tree.
+    #[getter(as_copy)]
+    width: u32,
+
+    /// Sibling nodes on the path from the root to the leaf.
+    path: TinyVec<MerkleHash>,
+}
+
+impl StrictSerialize for MerklePath {}
+impl StrictDeserialize for MerklePath {}
+
+impl MerklePath {
+    /// Constructs inclusion proof for a leaf at position `pos` of the tree
+    /// produced by [`MerkleHash::merklize`].
+    ///
+    /// Returns `None` if the position is outside the tree.
+    pub fn with(leaves: &impl MerkleLeaves, pos: u32) -> Option<Self> {
+        let nodes = leaves
+            .merkle_leaves()
+            .map(|leaf| leaf.commit_id())
+            .collect::<Vec<_>>();
+        Self::with_nodes(&nodes, pos)
+    }
+
+    /// Constructs inclusion proof for an annotated leaf at position `pos` of
+    /// the tree produced by [`MerkleHash::merklize_annotated`].
+    ///
+    /// Returns `None` if the position is outside the tree.
+    pub fn annotated<L: StrictEncode, A: StrictEncode>(
+        leaves: &[(L, A)],
+        pos: u32,
+    ) -> Option<Self> {
+        let nodes = leaves
+            .iter()
+            .map(|(leaf, annotation)| MerkleHash::annotated_leaf(leaf, annotation))
+            .collect::<Vec<_>>();
+        Self::with_nodes(&nodes, pos)
+    }
+
+    fn with_nodes(nodes: &[MerkleHash], pos: u32) -> Option<Self> {
+        let width = u32::try_from(nodes.len()).expect("too many merkle leaves (more than 2^31)");
+        if pos >= width {
+            return None;
+        }
+        let mut path = vec![];
+        if width > 1 {
+            Self::build_path(nodes, pos, u5::ZERO, width, &mut path);
+        }
+        let path = TinyVec::try_from(path).expect("tree depth never exceeds 32");
+        Some(Self { pos, width, path })
+    }
+
+    fn build_path(
+        nodes: &[MerkleHash],
+        pos: u32,
+        depth: u5,
+        base_width: u32,
+        path: &mut Vec<MerkleHash>,
+    ) {
+        let branch_width = nodes.len() as u32;
+        if branch_width <= 2 {
+            if branch_width == 2 {
+                path.push(nodes[1 - pos as usize]);
+            }
+            return;
+        }
+        let div = branch_width / 2 + branch_width % 2;
+        let (left, right) = nodes.split_at(div as usize);
+        if pos < div {
+            let sibling = right.iter().copied();
+            path.push(MerkleHash::_merklize(
+                sibling,
+                depth + 1,
+                branch_width - div,
+                base_width,
+                OddNode::Single,
+            ));
+            Self::build_path(left, pos, depth + 1, base_width, path);
+        } else {
+            let sibling = left.iter().copied();
+            path.push(MerkleHash::_merklize(sibling, depth + 1, div, base_width, OddNode::Single));
+            Self::build_path(right, pos - div, depth + 1, base_width, path);
+        }
+    }
+
+    /// Computes the tree root from the leaf node and the path.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the leaf position is outside the tree, or if the path length
+    /// doesn't match the depth of the tree (see [`MerklePathError`]).
+    pub fn convolve(&self, leaf: MerkleHash) -> Result<MerkleHash, MerklePathError> {
+        if self.pos >= self.width {
+            return Err(MerklePathError::InvalidPosition {
+                pos: self.pos,
+                width: self.width,
+            });
+        }
+        let mut path = self.path.iter().copied();
+        let root = if self.width == 1 {
+            leaf
+        } else {
+            self.convolve_branch(leaf, self.pos, u5::ZERO, self.width, &mut path)?
+        };
+        if path.next().is_some() {
+            return Err(MerklePathError::PathTooLong);
+        }
+        Ok(root)
+    }
+
+    fn convolve_branch(
+        &self,
+        leaf: MerkleHash,
+        pos: u32,
+        depth: u5,
+        branch_width: u32,
+        path: &mut impl Iterator<Item = MerkleHash>,
+    ) -> Result<MerkleHash, MerklePathError> {
+        if branch_width == 1 {
+            return Ok(MerkleHash::single(depth, self.width, leaf));
+        }
+        let sibling = path.next().ok_or(MerklePathError::PathTooShort)?;
+        if branch_width == 2 {
+            return Ok(match pos {
+                0 => MerkleHash::branches(depth, self.width, leaf, sibling),
+                _ => MerkleHash::branches(depth, self.width, sibling, leaf),
+            });
+        }
+        let div = branch_width / 2 + branch_width % 2;
+        Ok(if pos < div {
+            let branch = self.convolve_branch(leaf, pos, depth + 1, div, path)?;
+            MerkleHash::branches(depth, self.width, branch, sibling)
+        } else {
+            let branch =
+                self.convolve_branch(leaf, pos - div, depth + 1, branch_width - div, path)?;
+            MerkleHash::branches(depth, self.width, sibling, branch)
+        })
+    }
+
+    /// Verifies that the leaf node is included into the tree with the given
+    /// root. Malformed paths never verify.
+    pub fn verify(&self, leaf: MerkleHash, root: MerkleHash) -> bool {
+        self.convolve(leaf) == Ok(root)
+    }
+
+    /// Verifies that the annotated leaf is included into the tree with the
+    /// given root.
+    pub fn verify_annotated<L: StrictEncode, A: StrictEncode>(
+        &self,
+        leaf: &L,
+        annotation: &A,
+        root: MerkleHash,
+    ) -> bool {
+        self.convolve(MerkleHash::annotated_leaf(leaf, annotation)) == Ok(root)
+    }
+}
+
+/// Errors constructing [`SuperTree`] and verifying [`SuperProof`].
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum SuperTreeError {
+    /// sub-tree label `{0}` is used more than once.
+    DuplicateLabel(String),
+
+    /// sub-tree label `{0}` contains sub-tag separator.
+    InvalidLabel(String),
+
+    /// invalid merkle path.
+    ///
+    /// Details: {0}
+    #[from]
+    InvalidPath(MerklePathError),
+}
+
+/// Two-level merkle tree aggregating roots of labelled sub-trees into a
+/// super-root.
+///
+/// Each leaf of the super-tree is a hash of the sub-tree root tagged with the
+/// sub-tree label appended to [`SuperTree::TAG`] (see [`SuperTree::leaf`]).
+/// The leaves are ordered by their labels, making the aggregation
+/// deterministic.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct SuperTree {
+    subtrees: BTreeMap<String, MerkleHash>,
+}
+
+impl SuperTree {
+    /// Parent tag for the super-tree leaves, which are tagged with
+    /// `urn:ubideco:merkle:super-tree#2024-01-31/<label>`.
+    pub const TAG: &'static str = "urn:ubideco:merkle:super-tree#2024-01-31";
+
+    /// Constructs super-tree from `(label, sub_root)` pairs.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`SuperTreeError::DuplicateLabel`] if the same label is
+    /// repeated, and with [`SuperTreeError::InvalidLabel`] if a label contains
+    /// [`crate::SUBTAG_SEPARATOR`].
+    pub fn new<L: Into<String>>(
+        subtrees: impl IntoIterator<Item = (L, MerkleHash)>,
+    ) -> Result<Self, SuperTreeError> {
+        let mut tree = Self::default();
+        for (label, root) in subtrees {
+            let label = label.into();
+            if label.contains(SUBTAG_SEPARATOR) {
+                return Err(SuperTreeError::InvalidLabel(label));
+            }
+            if tree.subtrees.contains_key(&label) {
+                return Err(SuperTreeError::DuplicateLabel(label));
+            }
+            tree.subtrees.insert(label, root);
+        }
+        Ok(tree)
+    }
+
+    /// Computes the super-tree leaf for a sub-tree `label` and its `sub_root`.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`SuperTreeError::InvalidLabel`] if the label contains
+    /// [`crate::SUBTAG_SEPARATOR`].
+    pub fn leaf(label: &str, sub_root: MerkleHash) -> Result<MerkleHash, SuperTreeError> {
+        let mut engine = Sha256::from_subtag(Self::TAG, label)
+            .map_err(|_| SuperTreeError::InvalidLabel(label.to_owned()))?;
+        engine.input_raw(sub_root.as_slice());
+        Ok(engine.into())
+    }
+
+    fn leaves(&self) -> Vec<MerkleHash> {
+        self.subtrees
+            .iter()
+            .map(|(label, root)| {
+                Self::leaf(label, *root).expect("labels are validated by the constructor")
+            })
+            .collect()
+    }
+
+    /// Computes the super-root.
+    pub fn root(&self) -> MerkleHash {
+        MerkleHash::merklize_nodes(self.leaves().into_iter(), OddNode::Single)
+    }
+
+    /// Constructs proof of inclusion of a sub-tree leaf into the super-root,
+    /// extending the `sub_path` proof for the leaf in the sub-tree with the
+    /// given `label`.
+    ///
+    /// Returns `None` if there is no sub-tree with the `label`.
+    pub fn inclusion_proof(&self, label: &str, sub_path: MerklePath) -> Option<SuperProof> {
+        let pos = self.subtrees.keys().position(|l| l == label)?;
+        let super_path = MerklePath::with_nodes(&self.leaves(), pos as u32)?;
+        Some(SuperProof {
+            label: label.to_owned(),
+            sub_path,
+            super_path,
+        })
+    }
+}
+
+/// Proof of inclusion of a sub-tree leaf into the root of [`SuperTree`].
+#[derive(Getters, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SuperProof {
+    /// Label of the sub-tree.
+    label: String,
+    /// Path from the leaf to the root of the sub-tree.
+    sub_path: MerklePath,
+    /// Path from the sub-tree leaf to the super-root.
+    super_path: MerklePath,
+}
+
+impl SuperProof {
+    /// Computes the super-root from the sub-tree leaf node.
+    ///
+    /// # Errors
+    ///
+    /// Errors if some of the paths are malformed or the label is invalid (see
+    /// [`SuperTreeError`]).
+    pub fn convolve(&self, leaf: MerkleHash) -> Result<MerkleHash, SuperTreeError> {
+        let sub_root = self.sub_path.convolve(leaf)?;
+        let root = self
+            .super_path
+            .convolve(SuperTree::leaf(&self.label, sub_root)?)?;
+        Ok(root)
+    }
+
+    /// Verifies that the sub-tree leaf node is included into the super-tree
+    /// with the given `root`.
+    pub fn verify(&self, leaf: MerkleHash, root: MerkleHash) -> bool {
+        self.convolve(leaf) == Ok(root)
+    }
+}
+
 pub trait MerkleLeaves {
     type Leaf: CommitId<CommitmentId = MerkleHash>;
     type LeafIter<'tmp>: ExactSizeIterator<Item = Self::Leaf>
     where Self: 'tmp;
 
     fn merkle_leaves(&self) -> Self::LeafIter<'_>;
+
+    /// Constructs inclusion proof for the leaf at position `index` (see
+    /// [`MerklePath::with`]).
+    ///
+    /// Returns `None` if the position is outside the tree.
+    fn merkle_proof(&self, index: usize) -> Option<MerklePath>
+    where Self: Sized {
+        MerklePath::with(self, u32::try_from(index).ok()?)
+    }
 }
 
 impl<T, const MIN: usize> MerkleLeaves for Confined<Vec<T>, MIN, { u8::MAX as usize }>
@@ -262,6 +959,65 @@ where T: CommitId<CommitmentId = MerkleHash> + Copy
     fn merkle_leaves(&self) -> Self::LeafIter<'_> { self.iter().copied() }
 }
 
+/// Merkle leaves memoizing their [`MerkleHash::merklize`] root.
+///
+/// The root is computed on the first call to
+/// [`CachedMerkleSource::merkle_root`] and is reused until the leaves are
+/// accessed mutably with [`CachedMerkleSource::leaves_mut`], which drops the
+/// cached value.
+#[derive(Clone, Debug)]
+pub struct CachedMerkleSource<T: MerkleLeaves> {
+    leaves: T,
+    root: Cell<Option<MerkleHash>>,
+}
+
+impl<T: MerkleLeaves> CachedMerkleSource<T> {
+    /// Wraps the leaves, without computing their root.
+    pub fn new(leaves: T) -> Self {
+        Self {
+            leaves,
+            root: Cell::new(None),
+        }
+    }
+
+    /// Returns the wrapped leaves.
+    pub fn leaves(&self) -> &T { &self.leaves }
+
+    /// Returns mutable access to the wrapped leaves, dropping the cached root.
+    pub fn leaves_mut(&mut self) -> &mut T {
+        self.root.set(None);
+        &mut self.leaves
+    }
+
+    /// Returns the cached root, if it was already computed.
+    pub fn cached_root(&self) -> Option<MerkleHash> { self.root.get() }
+
+    /// Returns the merkle root of the leaves, computing it with
+    /// [`MerkleHash::merklize`] only if it is not cached yet.
+    pub fn merkle_root(&self) -> MerkleHash {
+        if let Some(root) = self.root.get() {
+            return root;
+        }
+        let root = MerkleHash::merklize(&self.leaves);
+        self.root.set(Some(root));
+        root
+    }
+
+    /// Releases the wrapped leaves.
+    pub fn into_inner(self) -> T { self.leaves }
+}
+
+impl<T: MerkleLeaves> From<T> for CachedMerkleSource<T> {
+    fn from(leaves: T) -> Self { Self::new(leaves) }
+}
+
+impl<T: MerkleLeaves> MerkleLeaves for CachedMerkleSource<T> {
+    type Leaf = T::Leaf;
+    type LeafIter<'tmp> = T::LeafIter<'tmp> where Self: 'tmp;
+
+    fn merkle_leaves(&self) -> Self::LeafIter<'_> { self.leaves.merkle_leaves() }
+}
+
 /// Helper struct to track depth when working with Merkle blocks.
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct MerkleBuoy<D: Copy + Eq + SubAssign<u8> + Default> {
@@ -317,3 +1073,365 @@ impl<D: Copy + Eq + SubAssign<u8> + Default> MerkleBuoy<D> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use amplify::confinement::TinyVec;
+    use amplify::ByteArray;
+    use strict_encoding::{StrictDeserialize, StrictSerialize, StrictWriter};
+
+    use super::*;
+    use crate::mpc::{Leaf, Message};
+
+    #[test]
+    fn from_leaf_hashes() {
+        for width in [0u16, 1, 2, 3, 5, 8, 13] {
+            let leaves = TinyVec::try_from(
+                (0..width)
+                    .map(|no| Leaf::entropy(no as u64, no as u32))
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+            let hashes = leaves.iter().map(Leaf::commit_id).collect::<Vec<_>>();
+            assert_eq!(MerkleHash::from_leaf_hashes(hashes), MerkleHash::merklize(&leaves));
+        }
+    }
+
+    #[test]
+    fn cached_merkle_source() {
+        let leaves = TinyVec::try_from(
+            (0..5)
+                .map(|no| Leaf::entropy(no, no as u32))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let mut cached = CachedMerkleSource::new(leaves.clone());
+        assert_eq!(cached.cached_root(), None);
+
+        let root = cached.merkle_root();
+        assert_eq!(root, MerkleHash::merklize(&leaves));
+        assert_eq!(cached.cached_root(), Some(root));
+        assert_eq!(cached.merkle_root(), root);
+        assert_eq!(MerkleHash::merklize(&cached), root);
+
+        cached.leaves_mut().push(Leaf::entropy(5, 5)).unwrap();
+        assert_eq!(cached.cached_root(), None);
+        let updated = cached.merkle_root();
+        assert_ne!(updated, root);
+        assert_eq!(updated, MerkleHash::merklize(cached.leaves()));
+        assert_eq!(cached.cached_root(), Some(updated));
+    }
+
+    fn annotated_leaves(width: u8) -> Vec<(Message, u64)> {
+        (0..width)
+            .map(|no| (Message::from_byte_array([no; 32]), no as u64 * 1000))
+            .collect()
+    }
+
+    #[test]
+    fn super_tree() {
+        let leaves_a = TinyVec::try_from(
+            (0..3)
+                .map(|no| Leaf::entropy(no, no as u32))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let leaves_b = TinyVec::try_from(
+            (0..5)
+                .map(|no| Leaf::entropy(no, no as u32))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let super_tree = SuperTree::new([
+            ("b", MerkleHash::merklize(&leaves_b)),
+            ("a", MerkleHash::merklize(&leaves_a)),
+        ])
+        .unwrap();
+        let root = super_tree.root();
+        assert_eq!(
+            root,
+            SuperTree::new([
+                ("a", MerkleHash::merklize(&leaves_a)),
+                ("b", MerkleHash::merklize(&leaves_b)),
+            ])
+            .unwrap()
+            .root()
+        );
+
+        for (label, leaves) in [("a", &leaves_a), ("b", &leaves_b)] {
+            for (pos, leaf) in leaves.iter().enumerate() {
+                let sub_path = MerklePath::with(leaves, pos as u32).unwrap();
+                let proof = super_tree.inclusion_proof(label, sub_path).unwrap();
+                assert!(proof.verify(leaf.commit_id(), root));
+                assert!(!proof.verify(Leaf::entropy(0xFF, 0).commit_id(), root));
+            }
+        }
+
+        // Same sub-tree under a different label doesn't verify
+        let sub_path = MerklePath::with(&leaves_a, 0).unwrap();
+        assert_eq!(super_tree.inclusion_proof("c", sub_path.clone()), None);
+        let proof = super_tree.inclusion_proof("a", sub_path).unwrap();
+        let relabelled = SuperProof {
+            label: s!("b"),
+            ..proof
+        };
+        assert!(!relabelled.verify(leaves_a[0].commit_id(), root));
+        let relabelled = SuperProof {
+            label: s!("a/b"),
+            ..relabelled
+        };
+        assert_eq!(
+            relabelled.convolve(leaves_a[0].commit_id()),
+            Err(SuperTreeError::InvalidLabel(s!("a/b")))
+        );
+    }
+
+    #[test]
+    fn super_tree_labels() {
+        let sub_root = MerkleHash::merklize(&TinyVec::<Leaf>::new());
+        assert_eq!(
+            SuperTree::new([("a", sub_root), ("b", sub_root), ("a", sub_root)]),
+            Err(SuperTreeError::DuplicateLabel(s!("a")))
+        );
+        assert_eq!(
+            SuperTree::new([("a", sub_root), ("b/c", sub_root)]),
+            Err(SuperTreeError::InvalidLabel(s!("b/c")))
+        );
+
+        let mut engine = Sha256::from_tag(format!("{}/a", SuperTree::TAG));
+        engine.input_raw(sub_root.as_slice());
+        assert_eq!(SuperTree::leaf("a", sub_root), Ok(MerkleHash::from(engine)));
+
+        // Raw labels are no longer used as tags
+        let mut engine = Sha256::from_tag("a");
+        engine.input_raw(sub_root.as_slice());
+        assert_ne!(SuperTree::leaf("a", sub_root), Ok(MerkleHash::from(engine)));
+    }
+
+    #[test]
+    fn inclusion_proofs() {
+        for width in 1..=17u64 {
+            let leaves = TinyVec::try_from(
+                (0..width)
+                    .map(|no| Leaf::entropy(no, 0))
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+            let root = MerkleHash::merklize(&leaves);
+            for (pos, leaf) in leaves.iter().enumerate() {
+                let proof = leaves.merkle_proof(pos).unwrap();
+                assert!(proof.verify(leaf.commit_id(), root));
+                if width > 1 {
+                    let other = leaves[(pos + 1) % width as usize];
+                    assert!(!proof.verify(other.commit_id(), root));
+                }
+            }
+            assert_eq!(leaves.merkle_proof(width as usize), None);
+        }
+    }
+
+    #[test]
+    fn malformed_paths() {
+        let leaves =
+            TinyVec::try_from((0..5).map(|no| Leaf::entropy(no, 0)).collect::<Vec<_>>()).unwrap();
+        let root = MerkleHash::merklize(&leaves);
+        let leaf = leaves[3].commit_id();
+        let proof = leaves.merkle_proof(3).unwrap();
+        assert_eq!(proof.convolve(leaf), Ok(root));
+
+        let mut truncated = proof.clone();
+        truncated.path = TinyVec::try_from(proof.path[..proof.path.len() - 1].to_vec()).unwrap();
+        assert_eq!(truncated.convolve(leaf), Err(MerklePathError::PathTooShort));
+        assert!(!truncated.verify(leaf, root));
+
+        let mut extended = proof.clone();
+        extended.path.push(root).unwrap();
+        assert_eq!(extended.convolve(leaf), Err(MerklePathError::PathTooLong));
+        assert!(!extended.verify(leaf, root));
+
+        let mut misplaced = proof.clone();
+        misplaced.pos = 5;
+        assert_eq!(
+            misplaced.convolve(leaf),
+            Err(MerklePathError::InvalidPosition { pos: 5, width: 5 })
+        );
+        assert!(!misplaced.verify(leaf, root));
+    }
+
+    #[test]
+    fn path_encoding() {
+        let leaves =
+            TinyVec::try_from((0..5).map(|no| Leaf::entropy(no, 0)).collect::<Vec<_>>()).unwrap();
+        let proof = leaves.merkle_proof(3).unwrap();
+        let data = proof
+            .to_strict_serialized::<{ u16::MAX as usize }>()
+            .unwrap();
+        assert_eq!(data.len(), 4 + 4 + 1 + 32 * proof.path.len());
+        let decoded = MerklePath::from_strict_serialized::<{ u16::MAX as usize }>(data).unwrap();
+        assert_eq!(decoded, proof);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&proof).unwrap();
+            assert_eq!(serde_json::from_str::<MerklePath>(&json).unwrap(), proof);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn merklize_parallel() {
+        use amplify::confinement::LargeVec;
+
+        for width in (0..=130u64).chain([1000, 4096, 4097]) {
+            let leaves = LargeVec::try_from(
+                (0..width)
+                    .map(|no| Leaf::entropy(no.wrapping_mul(0x9e37_79b9_7f4a_7c15), no as u32))
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+            assert_eq!(MerkleHash::merklize_parallel(&leaves), MerkleHash::merklize(&leaves));
+        }
+    }
+
+    #[test]
+    fn merkle_root() {
+        enum ProtoA {}
+        impl CommitmentProtocol for ProtoA {}
+
+        let leaves =
+            TinyVec::try_from((0..5).map(|no| Leaf::entropy(no, 0)).collect::<Vec<_>>()).unwrap();
+        let root = MerkleRoot::<ProtoA>::merklize(&leaves);
+        assert_eq!(root.merkle_hash(), MerkleHash::merklize(&leaves));
+        assert_eq!(root.to_string(), MerkleHash::merklize(&leaves).to_string());
+        assert_eq!(MerkleRoot::<ProtoA>::from_str(&root.to_string()).unwrap(), root);
+        let data = root.to_strict_serialized::<32>().unwrap();
+        assert_eq!(data.as_slice(), root.merkle_hash().as_slice());
+        assert_eq!(MerkleRoot::<ProtoA>::from_strict_serialized::<32>(data).unwrap(), root);
+    }
+
+    #[test]
+    fn odd_node_strategies() {
+        let leaves = |width: u64| {
+            TinyVec::try_from(
+                (0..width)
+                    .map(|no| Leaf::entropy(no, 0))
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap()
+        };
+        let strategies = [OddNode::Single, OddNode::Duplicate, OddNode::ZeroPad];
+
+        let three = leaves(3);
+        let roots = strategies.map(|odd| MerkleHash::merklize_with(&three, odd));
+        assert_eq!(roots[0], MerkleHash::merklize(&three));
+        assert_ne!(roots[0], roots[1]);
+        assert_ne!(roots[0], roots[2]);
+        assert_ne!(roots[1], roots[2]);
+
+        let four = leaves(4);
+        let roots = strategies.map(|odd| MerkleHash::merklize_with(&four, odd));
+        assert_eq!(roots, [MerkleHash::merklize(&four); 3]);
+    }
+
+    #[test]
+    fn empty_merklization() {
+        let empty = TinyVec::<Leaf>::new();
+        let root = MerkleHash::merklize(&empty);
+        assert_eq!(root, MerkleHash::void(0u8, 0u32));
+        assert_eq!(
+            root.to_string(),
+            "5442b1a1ae7badee5391857589104a594e466ffc6fd6484d2890b466537f7f7a"
+        );
+        assert_eq!(MerkleHash::merklize_annotated::<Message, u64>(&[]), root);
+        #[cfg(feature = "rayon")]
+        assert_eq!(MerkleHash::merklize_parallel(&empty), root);
+
+        let single = TinyVec::try_from(vec![Leaf::entropy(0, 0)]).unwrap();
+        assert_ne!(MerkleHash::merklize(&single), root);
+        let zero = [(Message::from_byte_array([0u8; 32]), 0u64)];
+        assert_ne!(MerkleHash::merklize_annotated(&zero), root);
+        assert_eq!(MerkleHash::merklize_shaped(&empty).width, 0);
+    }
+
+    #[test]
+    fn shape_commitment() {
+        let leaf1 = Leaf::entropy(1, 0);
+        let leaf2 = Leaf::entropy(2, 1);
+        let leaves = TinyVec::try_from(vec![leaf1, leaf2]).unwrap();
+        // Single-leaf tree, which leaf is the root node of the first tree
+        let node = MerkleNode::branches(0u8, 2u32, leaf1.commit_id(), leaf2.commit_id());
+        let nodes = TinyVec::try_from(vec![node]).unwrap();
+
+        assert_eq!(MerkleHash::merklize(&leaves), MerkleHash::merklize(&nodes));
+
+        let shape1 = MerkleHash::merklize_shaped(&leaves);
+        let shape2 = MerkleHash::merklize_shaped(&nodes);
+        assert_eq!(shape1.root, shape2.root);
+        assert_eq!((shape1.width, shape1.depth), (2, 1));
+        assert_eq!((shape2.width, shape2.depth), (1, 0));
+        assert_ne!(shape1.commit_id(), shape2.commit_id());
+    }
+
+    #[test]
+    fn annotated_proofs() {
+        for width in 1..=9 {
+            let leaves = annotated_leaves(width);
+            let root = MerkleHash::merklize_annotated(&leaves);
+            for (pos, (leaf, annotation)) in leaves.iter().enumerate() {
+                let proof = MerklePath::annotated(&leaves, pos as u32).unwrap();
+                assert!(proof.verify_annotated(leaf, annotation, root));
+                assert!(!proof.verify_annotated(leaf, &(annotation + 1), root));
+            }
+            assert_eq!(MerklePath::annotated(&leaves, width as u32), None);
+        }
+    }
+
+    #[test]
+    fn annotated_leaf_tag() {
+        // Remaining fields of `MerkleNode` after the branching
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+        struct NodeRest {
+            depth: u8,
+            width: u256,
+            node1: MerkleHash,
+            node2: MerkleHash,
+        }
+
+        assert_eq!(ANNOTATED_LEAF_TAG, crate::subtag(MerkleHash::TAG, "annotated-leaf").unwrap());
+
+        // Annotation followed by the leaf serialize into the same bytes as the
+        // branch node
+        let node = MerkleNode::branches(1u8, 3u32, VIRTUAL_LEAF, ZERO_LEAF);
+        let rest = NodeRest {
+            depth: node.depth,
+            width: node.width,
+            node1: node.node1,
+            node2: node.node2,
+        };
+        fn serialize(value: &impl StrictEncode) -> Vec<u8> {
+            value
+                .strict_encode(StrictWriter::in_memory::<128>())
+                .unwrap()
+                .unbox()
+                .unconfine()
+        }
+        let mut bytes = serialize(&node.branching);
+        bytes.extend(serialize(&rest));
+        assert_eq!(bytes, serialize(&node));
+
+        assert_ne!(MerkleHash::annotated_leaf(&rest, &node.branching), node.commit_id());
+    }
+
+    #[test]
+    fn annotated_leaf_commits_annotation() {
+        let leaf = Message::from_byte_array([0xAB; 32]);
+        assert_ne!(
+            MerkleHash::annotated_leaf(&leaf, &1u64),
+            MerkleHash::annotated_leaf(&leaf, &2u64)
+        );
+    }
+}
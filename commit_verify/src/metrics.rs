@@ -0,0 +1,115 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Thread-local instrumentation counters for performance analysis of
+//! commitment procedures.
+
+use std::cell::Cell;
+
+thread_local! {
+    static TAGGED_HASHES: Cell<u64> = const { Cell::new(0) };
+    static COMMITMENTS: Cell<u64> = const { Cell::new(0) };
+    static CONVOLVE_VERIFICATIONS: Cell<u64> = const { Cell::new(0) };
+    static EMBED_VERIFICATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Values of the counters for the current thread.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Snapshot {
+    /// Number of tagged hashers created with [`crate::DigestExt::from_tag`].
+    pub tagged_hashes: u64,
+    /// Number of commitments produced with [`crate::CommitId::commit_id`].
+    pub commitments: u64,
+    /// Number of convolve commitments verified with
+    /// [`crate::ConvolveCommitProof`] verification methods, each repeating
+    /// [`crate::ConvolveCommit::convolve_commit`] procedure.
+    pub convolve_verifications: u64,
+    /// Number of embed commitments verified with
+    /// [`crate::EmbedCommitVerify::verify`], each repeating
+    /// [`crate::EmbedCommitVerify::embed_commit`] procedure.
+    pub embed_verifications: u64,
+}
+
+/// Returns the current values of the counters for the current thread.
+pub fn snapshot() -> Snapshot {
+    Snapshot {
+        tagged_hashes: TAGGED_HASHES.with(Cell::get),
+        commitments: COMMITMENTS.with(Cell::get),
+        convolve_verifications: CONVOLVE_VERIFICATIONS.with(Cell::get),
+        embed_verifications: EMBED_VERIFICATIONS.with(Cell::get),
+    }
+}
+
+/// Resets the counters for the current thread.
+pub fn reset() {
+    TAGGED_HASHES.with(|counter| counter.set(0));
+    COMMITMENTS.with(|counter| counter.set(0));
+    CONVOLVE_VERIFICATIONS.with(|counter| counter.set(0));
+    EMBED_VERIFICATIONS.with(|counter| counter.set(0));
+}
+
+pub(crate) fn count_tagged_hash() { TAGGED_HASHES.with(|counter| counter.set(counter.get() + 1)); }
+
+pub(crate) fn count_commitment() { COMMITMENTS.with(|counter| counter.set(counter.get() + 1)); }
+
+pub(crate) fn count_convolve_verification() {
+    CONVOLVE_VERIFICATIONS.with(|counter| counter.set(counter.get() + 1));
+}
+
+pub(crate) fn count_embed_verification() {
+    EMBED_VERIFICATIONS.with(|counter| counter.set(counter.get() + 1));
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::TinyVec;
+
+    use super::*;
+    use crate::mpc::Leaf;
+    use crate::{CommitId, MerkleHash};
+
+    #[test]
+    fn counters() {
+        reset();
+        for no in 0..10 {
+            Leaf::entropy(no, 0).commit_id();
+        }
+        assert_eq!(snapshot(), Snapshot {
+            tagged_hashes: 10,
+            commitments: 10,
+            ..default!()
+        });
+
+        // 4 leaves and 3 nodes
+        reset();
+        let leaves =
+            TinyVec::try_from((0..4).map(|no| Leaf::entropy(no, 0)).collect::<Vec<_>>()).unwrap();
+        MerkleHash::merklize(&leaves);
+        assert_eq!(snapshot(), Snapshot {
+            tagged_hashes: 7,
+            commitments: 7,
+            ..default!()
+        });
+
+        reset();
+        assert_eq!(snapshot(), Snapshot::default());
+    }
+}
@@ -19,14 +19,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::hash::{Hash, Hasher};
+
 use amplify::confinement::MediumOrdMap;
 use amplify::num::u5;
-use amplify::{Bytes32, FromSliceError, Wrapper};
+use amplify::{ByteArray, Bytes32, FromSliceError, Wrapper};
 use sha2::Sha256;
 use strict_encoding::StrictDumb;
 
 use crate::merkle::MerkleHash;
-use crate::{CommitmentId, DigestExt};
+use crate::mpc::Error;
+use crate::{ct_eq, CommitEncode, CommitId, CommitmentId, DigestExt};
 
 pub const MPC_MINIMAL_DEPTH: u5 = u5::with(3);
 
@@ -80,6 +83,17 @@ impl Message {
     pub fn copy_from_slice(slice: &[u8]) -> Result<Self, FromSliceError> {
         Bytes32::copy_from_slice(slice).map(Self)
     }
+
+    /// Constructs message from the commitment to the client-side-validated
+    /// `data` of arbitrary size. The message bytes are equal to the
+    /// [`CommitId::commit_id`] of the data.
+    pub fn commit_to<T>(data: &T) -> Self
+    where
+        T: CommitEncode,
+        T::CommitmentId: ByteArray<32>,
+    {
+        data.commit_id().to_byte_array().into()
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, From)]
@@ -87,6 +101,11 @@ impl Message {
 #[strict_type(lib = crate::LIB_NAME_COMMIT_VERIFY, tags = custom)]
 #[derive(CommitEncode)]
 #[commit_encode(crate = crate, strategy = strict, id = MerkleHash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
 pub enum Leaf {
     // We use this constant since we'd like to be distinct from NodeBranching values
     #[strict_type(tag = 0x10)]
@@ -117,7 +136,7 @@ impl StrictDumb for Leaf {
 /// [`super::MerkleBlock`].
 ///
 /// [LNPBP-4]: https://github.com/LNP-BP/LNPBPs/blob/master/lnpbp-0004.md
-#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Debug, From)]
 #[wrapper(Deref, BorrowSlice, Display, FromStr, Hex, Index, RangeOps)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
 #[strict_type(lib = crate::LIB_NAME_COMMIT_VERIFY)]
@@ -136,6 +155,17 @@ impl CommitmentId for Commitment {
     const TAG: &'static str = "urn:ubideco:mpc:commitment#2024-01-31";
 }
 
+impl PartialEq for Commitment {
+    /// Compares the hashes in constant time (see [`crate::ct_eq`]).
+    fn eq(&self, other: &Self) -> bool { ct_eq(self.as_slice(), other.as_slice()) }
+}
+
+impl Eq for Commitment {}
+
+impl Hash for Commitment {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+}
+
 impl Commitment {
     pub fn copy_from_slice(slice: &[u8]) -> Result<Self, FromSliceError> {
         Bytes32::copy_from_slice(slice).map(Self)
@@ -175,4 +205,61 @@ impl MultiSource {
             ..default!()
         }
     }
+
+    /// Constructs multi-message source from pairs of protocol ids and their
+    /// messages, using [`MPC_MINIMAL_DEPTH`] and no static entropy.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::DuplicateProtocol`] if some of the messages share
+    /// the same protocol id, and with [`Error::TooManyMessages`] if the number
+    /// of the messages exceeds the limit of [`MessageMap`].
+    pub fn with_messages(
+        messages: impl IntoIterator<Item = (ProtocolId, Message)>,
+    ) -> Result<Self, Error> {
+        let mut source = MultiSource::default();
+        for (protocol_id, message) in messages {
+            source.insert_message(protocol_id, message)?;
+        }
+        Ok(source)
+    }
+
+    /// Adds message under the given `protocol_id`, refusing to replace a
+    /// message already present for that protocol id.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::DuplicateProtocol`] if a message for the protocol
+    /// id is already present, and with [`Error::TooManyMessages`] if the number
+    /// of the messages exceeds the limit of [`MessageMap`].
+    pub fn insert_message(
+        &mut self,
+        protocol_id: ProtocolId,
+        message: Message,
+    ) -> Result<(), Error> {
+        if self.messages.contains_key(&protocol_id) {
+            return Err(Error::DuplicateProtocol(protocol_id));
+        }
+        self.messages
+            .insert(protocol_id, message)
+            .map_err(|_| Error::TooManyMessages(self.messages.len() + 1))?;
+        Ok(())
+    }
+
+    /// Adds message committing to the client-side-validated `data` under the
+    /// given `protocol_id` (see [`Message::commit_to`]), refusing to replace a
+    /// message already present for that protocol id.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::DuplicateProtocol`] if a message for the protocol
+    /// id is already present, and with [`Error::TooManyMessages`] if the number
+    /// of the messages exceeds the limit of [`MessageMap`].
+    pub fn insert_commitment<T>(&mut self, protocol_id: ProtocolId, data: &T) -> Result<(), Error>
+    where
+        T: CommitEncode,
+        T::CommitmentId: ByteArray<32>,
+    {
+        self.insert_message(protocol_id, Message::commit_to(data))
+    }
 }
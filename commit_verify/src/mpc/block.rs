@@ -32,8 +32,8 @@ use crate::id::CommitId;
 use crate::merkle::{MerkleBuoy, MerkleHash};
 use crate::mpc::atoms::Leaf;
 use crate::mpc::tree::protocol_id_pos;
-use crate::mpc::{Commitment, MerkleTree, Message, MessageMap, Proof, ProtocolId};
-use crate::{Conceal, LIB_NAME_COMMIT_VERIFY};
+use crate::mpc::{Commitment, MerkleTree, Message, MessageMap, Proof, ProtocolId, SlotCache};
+use crate::{Conceal, VerifyError, LIB_NAME_COMMIT_VERIFY};
 
 /// commitment under protocol id {0} is absent from the known part of a given
 /// LNPBP-4 Merkle block.
@@ -58,6 +58,36 @@ pub struct InvalidProof {
     width: u32,
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum InclusionError {
+    #[from]
+    #[display(inner)]
+    InvalidProof(InvalidProof),
+
+    /// inclusion proof doesn't match the provided commitment.
+    CommitmentMismatch,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum NonInclusionError {
+    /// commitment under protocol id {0} is present in the LNPBP-4 Merkle
+    /// block.
+    Included(ProtocolId),
+
+    /// tree slot for protocol id {0} is concealed in the given LNPBP-4 Merkle
+    /// block, so its absence can't be proven.
+    SlotConcealed(ProtocolId),
+
+    #[from]
+    #[display(inner)]
+    InvalidProof(InvalidProof),
+
+    /// non-inclusion proof doesn't match the provided commitment.
+    CommitmentMismatch,
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
 #[display(doc_comments)]
 pub enum MergeError {
@@ -130,6 +160,12 @@ impl TreeNode {
 }
 
 /// Partially-concealed merkle tree data.
+///
+/// With `serde` feature, the block is represented as a structure with `depth`,
+/// `cofactor`, `cross_section` and `entropy` fields, where each of the
+/// cross-section nodes is either a `CommitmentLeaf` with hex-encoded
+/// `protocol_id` and `message`, or a `ConcealedNode` with its `depth` and
+/// hex-encoded `hash`.
 #[derive(Getters, Clone, PartialEq, Eq, Hash, Debug)]
 #[derive(StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
@@ -212,20 +248,29 @@ impl MerkleBlock {
         protocol_id: ProtocolId,
         message: Message,
     ) -> Result<Self, InvalidProof> {
-        let path = proof.as_path();
-        let mut pos = proof.pos;
-        let mut width = proof.width();
-
-        let expected = protocol_id_pos(protocol_id, proof.cofactor, width);
-        if expected != pos {
+        let expected = protocol_id_pos(protocol_id, proof.cofactor, proof.width());
+        if expected != proof.pos {
             return Err(InvalidProof {
                 protocol_id,
                 expected,
-                actual: pos,
-                width,
+                actual: proof.pos,
+                width: proof.width(),
             });
         }
 
+        Ok(MerkleBlock::with_node(proof, TreeNode::CommitmentLeaf {
+            protocol_id,
+            message,
+        }))
+    }
+
+    /// Constructs merkle block from a merkle proof, placing the provided
+    /// `node` into the tree slot at the position of the proof.
+    fn with_node(proof: &MerkleProof, node: TreeNode) -> Self {
+        let path = proof.as_path();
+        let mut pos = proof.pos;
+        let mut width = proof.width();
+
         let mut dir = Vec::with_capacity(path.len());
         let mut rev = Vec::with_capacity(path.len());
         for (depth, hash) in path.iter().enumerate() {
@@ -244,20 +289,17 @@ impl MerkleBlock {
 
         let mut cross_section = Vec::with_capacity(path.len() + 1);
         cross_section.extend(dir);
-        cross_section.push(TreeNode::CommitmentLeaf {
-            protocol_id,
-            message,
-        });
+        cross_section.push(node);
         cross_section.extend(rev.into_iter().rev());
         let cross_section =
             NonEmptyVec::try_from(cross_section).expect("tree width guarantees are broken");
 
-        Ok(MerkleBlock {
+        MerkleBlock {
             depth: u5::with(path.len() as u8),
             cofactor: proof.cofactor,
             cross_section,
             entropy: None,
-        })
+        }
     }
 
     /// Conceals all commitments in the block except for the commitment under
@@ -561,14 +603,141 @@ Changed commitment id: {}",
         self.clone().into_merkle_proof(protocol_id)
     }
 
+    /// Constructs proof that there is no commitment under the given
+    /// `protocol_id` in the current Merkle block.
+    ///
+    /// The proof reveals the content of the tree slot the `protocol_id` is
+    /// mapped to, which must be either an entropy placeholder or a commitment
+    /// under some other protocol.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`NonInclusionError::Included`] if the block contains a
+    /// commitment under the `protocol_id`, and with
+    /// [`NonInclusionError::SlotConcealed`] if the slot content or the entropy
+    /// is not known.
+    pub fn non_inclusion_proof(
+        &self,
+        protocol_id: ProtocolId,
+    ) -> Result<NonInclusionProof, NonInclusionError> {
+        let pos = self.protocol_id_pos(protocol_id);
+        let index = self.slot_index(pos);
+        let leaf = match self.cross_section[index] {
+            TreeNode::CommitmentLeaf { protocol_id: p, .. } if p == protocol_id => {
+                return Err(NonInclusionError::Included(protocol_id));
+            }
+            TreeNode::CommitmentLeaf {
+                protocol_id,
+                message,
+            } => Leaf::inhabited(protocol_id, message),
+            TreeNode::ConcealedNode { depth, hash } => match self.entropy {
+                Some(entropy)
+                    if depth == self.depth && hash == Leaf::entropy(entropy, pos).commit_id() =>
+                {
+                    Leaf::entropy(entropy, pos)
+                }
+                _ => return Err(NonInclusionError::SlotConcealed(protocol_id)),
+            },
+        };
+
+        // A malformed (for instance, decoded from untrusted data) block may
+        // contain a leaf for the `protocol_id` outside of its slot. Such block
+        // does contain the commitment, and a merkle path can't be constructed
+        // for it.
+        if self.cross_section.iter().any(|node| {
+            matches!(node, TreeNode::CommitmentLeaf { protocol_id: p, .. } if *p == protocol_id)
+        }) {
+            return Err(NonInclusionError::Included(protocol_id));
+        }
+
+        // Merkle path depends only on the sibling nodes, so we can put a
+        // placeholder leaf into the slot and reuse the inclusion proof procedure.
+        // This can't fail since, as checked above, the placeholder becomes the
+        // only leaf for the `protocol_id` in the block.
+        let mut block = self.clone();
+        block.cross_section[index] = TreeNode::CommitmentLeaf {
+            protocol_id,
+            message: default!(),
+        };
+        let proof = block
+            .into_merkle_proof(protocol_id)
+            .expect("placeholder is the only leaf for the protocol");
+        debug_assert_eq!(proof.pos, pos, "MerkleBlock non-inclusion procedure is broken");
+
+        Ok(NonInclusionProof { proof, leaf })
+    }
+
+    /// Returns index of the cross-section node covering tree slot at `pos`.
+    fn slot_index(&self, pos: u32) -> usize {
+        let mut offset = 0u32;
+        for (index, node) in self.cross_section.iter().enumerate() {
+            let height = self.depth.to_u8() - node.depth_or(self.depth).to_u8();
+            let span = 2u32.pow(height as u32);
+            if pos - offset < span {
+                return index;
+            }
+            offset += span;
+        }
+        unreachable!("tree width guarantees are broken")
+    }
+
     /// Computes position for a given `protocol_id` within the tree leaves.
     pub fn protocol_id_pos(&self, protocol_id: ProtocolId) -> u32 {
         protocol_id_pos(protocol_id, self.cofactor, self.width())
     }
 
+    /// Computes position for a given `protocol_id` within the tree leaves
+    /// using a shared [`SlotCache`].
+    pub fn protocol_id_pos_cached(&self, protocol_id: ProtocolId, cache: &mut SlotCache) -> u32 {
+        cache.slot(protocol_id, self.width(), self.cofactor)
+    }
+
     /// Computes the width of the merkle tree.
     pub fn width(&self) -> u32 { 2u32.pow(self.depth.to_u8() as u32) }
 
+    /// Verifies that the block commits to exactly the given set of `messages`:
+    /// each of the messages must be revealed in the tree slot of its protocol,
+    /// and all other slots must be revealed entropy placeholders.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`VerifyError::InvalidCommitment`] if some of the
+    /// `messages` are not present in their slots, if the block contains
+    /// commitments under protocols not present in `messages`, or if some of
+    /// the slots are concealed or the entropy is unknown, such that the
+    /// absence of other commitments can't be verified.
+    pub fn verify_exact(
+        &self,
+        messages: &BTreeMap<ProtocolId, Message>,
+    ) -> Result<(), VerifyError> {
+        let mut matched = 0usize;
+        // Only leaf-level nodes are accepted below, so the index of a node in
+        // the cross-section is its slot position.
+        for (pos, node) in self.cross_section.iter().enumerate() {
+            let pos = pos as u32;
+            match *node {
+                TreeNode::CommitmentLeaf {
+                    protocol_id,
+                    message,
+                } if messages.get(&protocol_id) == Some(&message) &&
+                    self.protocol_id_pos(protocol_id) == pos =>
+                {
+                    matched += 1;
+                }
+                TreeNode::ConcealedNode { depth, hash }
+                    if depth == self.depth &&
+                        self.entropy
+                            .map(|entropy| Leaf::entropy(entropy, pos).commit_id()) ==
+                            Some(hash) => {}
+                _ => return Err(VerifyError::InvalidCommitment),
+            }
+        }
+        if matched != messages.len() {
+            return Err(VerifyError::InvalidCommitment);
+        }
+        Ok(())
+    }
+
     /// Constructs [`MessageMap`] for revealed protocols and messages.
     pub fn to_known_message_map(&self) -> MessageMap {
         Confined::try_from_iter(
@@ -660,14 +829,149 @@ impl MerkleProof {
         let block = MerkleBlock::with(self, protocol_id, message)?;
         Ok(block.commit_id())
     }
+
+    /// Verifies that the `commitment` contains the `message` under the given
+    /// `protocol_id`.
+    ///
+    /// The proof reveals neither the messages under other protocols nor the
+    /// entropy used for the empty tree slots, which are committed to only by
+    /// the hashes in the merkle path.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`InclusionError::InvalidProof`] if the proof position
+    /// doesn't match the `protocol_id` slot, and with
+    /// [`InclusionError::CommitmentMismatch`] if the proof doesn't match the
+    /// `commitment`.
+    pub fn verify(
+        &self,
+        protocol_id: ProtocolId,
+        message: Message,
+        commitment: Commitment,
+    ) -> Result<(), InclusionError> {
+        if self.convolve(protocol_id, message)? != commitment {
+            return Err(InclusionError::CommitmentMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// A proof that there is no commitment under some protocol id in the LNPBP-4
+/// Merkle tree.
+#[derive(Getters, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
+pub struct NonInclusionProof {
+    /// Merkle proof for the tree slot the protocol id is mapped to.
+    proof: MerkleProof,
+
+    /// Content of the tree slot: either an entropy placeholder or a
+    /// commitment under some other protocol.
+    #[getter(as_copy)]
+    leaf: Leaf,
+}
+
+impl NonInclusionProof {
+    /// Convolves the proof for the absence of a commitment under the given
+    /// `protocol_id`, producing [`Commitment`].
+    pub fn convolve(&self, protocol_id: ProtocolId) -> Result<Commitment, NonInclusionError> {
+        let pos = self.proof.pos;
+        let width = self.proof.width();
+        let expected = protocol_id_pos(protocol_id, self.proof.cofactor, width);
+        if expected != pos {
+            return Err(InvalidProof {
+                protocol_id,
+                expected,
+                actual: pos,
+                width,
+            }
+            .into());
+        }
+
+        let node = match self.leaf {
+            Leaf::Inhabited { protocol, .. } if protocol == protocol_id => {
+                return Err(NonInclusionError::Included(protocol_id));
+            }
+            Leaf::Inhabited { protocol, message } => TreeNode::CommitmentLeaf {
+                protocol_id: protocol,
+                message,
+            },
+            Leaf::Entropy { pos: actual, .. } if actual != pos => {
+                return Err(InvalidProof {
+                    protocol_id,
+                    expected,
+                    actual,
+                    width,
+                }
+                .into());
+            }
+            Leaf::Entropy { .. } => TreeNode::ConcealedNode {
+                depth: u5::with(self.proof.depth()),
+                hash: self.leaf.commit_id(),
+            },
+        };
+
+        Ok(MerkleBlock::with_node(&self.proof, node).commit_id())
+    }
+
+    /// Verifies that the `commitment` doesn't contain a commitment under the
+    /// given `protocol_id`.
+    pub fn verify(
+        &self,
+        protocol_id: ProtocolId,
+        commitment: Commitment,
+    ) -> Result<(), NonInclusionError> {
+        if self.convolve(protocol_id)? != commitment {
+            return Err(NonInclusionError::CommitmentMismatch);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use amplify::confinement::SmallBlob;
+    use amplify::num::u256;
+    use amplify::ByteArray;
+
     use super::*;
     use crate::mpc::tree::test_helpers::{
         make_det_messages, make_random_messages, make_random_tree,
     };
+    use crate::mpc::MultiSource;
+    use crate::{StrictHash, TryCommitVerify};
+
+    #[test]
+    fn long_message() {
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+        #[derive(CommitEncode)]
+        #[commit_encode(crate = crate, strategy = strict, id = StrictHash)]
+        struct LongMessage(SmallBlob);
+
+        let data = LongMessage(SmallBlob::try_from(vec![0xA5; 1000]).unwrap());
+        let protocol_id = ProtocolId::from([0x01; 32]);
+        let message = Message::commit_to(&data);
+        assert_eq!(message.to_byte_array(), data.commit_id().to_byte_array());
+
+        let mut source = MultiSource::with_static_entropy(0);
+        source.insert_commitment(protocol_id, &data).unwrap();
+        source
+            .messages
+            .insert(ProtocolId::from([0x02; 32]), Message::from([0x02; 32]))
+            .unwrap();
+        let tree = MerkleTree::try_commit(&source).unwrap();
+        let commitment = tree.commit_id();
+
+        let proof = MerkleBlock::from(&tree)
+            .into_merkle_proof(protocol_id)
+            .unwrap();
+        assert_eq!(proof.convolve(protocol_id, Message::commit_to(&data)), Ok(commitment));
+        let altered = LongMessage(SmallBlob::try_from(vec![0xA5; 999]).unwrap());
+        assert_ne!(proof.convolve(protocol_id, Message::commit_to(&altered)), Ok(commitment));
+    }
 
     #[test]
     fn entropy() {
@@ -737,7 +1041,7 @@ mod test {
                 .map(|pid| mpc_block.to_merkle_proof(*pid).unwrap())
                 .collect::<Vec<_>>();
 
-            let mut iter = proofs.iter().zip(msgs.into_iter());
+            let mut iter = proofs.iter().zip(msgs);
             let (proof, (pid, msg)) = iter.next().unwrap();
             let mut merged_block = MerkleBlock::with(proof, pid, msg).unwrap();
             for (proof, (pid, msg)) in iter {
@@ -756,4 +1060,209 @@ mod test {
             assert_eq!(merged_block.commit_id(), mpc_tree.commit_id());
         }
     }
+
+    #[test]
+    fn verify_exact() {
+        let mut source = MultiSource::with_static_entropy(0xC0FFEE);
+        for no in 0..5u8 {
+            source
+                .insert_message(ProtocolId::from([no; 32]), Message::from([0xA0 | no; 32]))
+                .unwrap();
+        }
+        let msgs = source.messages.as_inner().clone();
+        let tree = MerkleTree::try_commit(&source).unwrap();
+        let block = MerkleBlock::from(&tree);
+        assert_eq!(block.verify_exact(&msgs), Ok(()));
+
+        let mut missing = msgs.clone();
+        missing.insert(absent_protocol_id(&tree, false), Message::from([0xFF; 32]));
+        assert_eq!(block.verify_exact(&missing), Err(VerifyError::InvalidCommitment));
+
+        let mut extra = msgs.clone();
+        extra.pop_first();
+        assert_eq!(block.verify_exact(&extra), Err(VerifyError::InvalidCommitment));
+
+        let mut altered = msgs.clone();
+        altered.insert(ProtocolId::from([0; 32]), Message::from([0xFF; 32]));
+        assert_eq!(block.verify_exact(&altered), Err(VerifyError::InvalidCommitment));
+
+        let mut concealed = block.clone();
+        concealed.entropy = None;
+        assert_eq!(concealed.verify_exact(&msgs), Err(VerifyError::InvalidCommitment));
+        assert_eq!(block.conceal().verify_exact(&msgs), Err(VerifyError::InvalidCommitment));
+    }
+
+    #[test]
+    fn inclusion_proof() {
+        let mut source = MultiSource::with_static_entropy(0xC0FFEE);
+        for no in 0..5u8 {
+            source
+                .insert_message(ProtocolId::from([no; 32]), Message::from([0xA0 | no; 32]))
+                .unwrap();
+        }
+        let tree = MerkleTree::try_commit(&source).unwrap();
+        let commitment = tree.commit_id();
+        let block = MerkleBlock::from(&tree);
+
+        for (protocol_id, message) in source.messages.iter() {
+            let proof = block.to_merkle_proof(*protocol_id).unwrap();
+            assert_eq!(proof.pos(), block.protocol_id_pos(*protocol_id));
+            assert_eq!(proof.verify(*protocol_id, *message, commitment), Ok(()));
+            assert_eq!(
+                proof.verify(*protocol_id, Message::from([0xFF; 32]), commitment),
+                Err(InclusionError::CommitmentMismatch)
+            );
+            let protocol_id = absent_protocol_id(&tree, false);
+            assert!(matches!(
+                proof.verify(protocol_id, *message, commitment),
+                Err(InclusionError::InvalidProof(_))
+            ));
+        }
+
+        let other = make_random_tree(&make_random_messages(5));
+        let (protocol_id, message) = source.messages.first_key_value().unwrap();
+        let proof = block.to_merkle_proof(*protocol_id).unwrap();
+        assert_eq!(
+            proof.verify(*protocol_id, *message, other.commit_id()),
+            Err(InclusionError::CommitmentMismatch)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_json() {
+        let mut source = MultiSource::with_static_entropy(0xC0FFEE);
+        for no in 0..3u8 {
+            source
+                .insert_message(ProtocolId::from([no; 32]), Message::from([0xA0 | no; 32]))
+                .unwrap();
+        }
+        let tree = MerkleTree::try_commit(&source).unwrap();
+        let mut block = MerkleBlock::from(&tree);
+        block.conceal_except([ProtocolId::from([0; 32])]).unwrap();
+        let json = serde_json::to_string(&block).unwrap();
+        assert_eq!(
+            json,
+            r#"{"depth":3,"cofactor":0,"cross_section":[{"CommitmentLeaf":{"protocol_id":"0000000000000000000000000000000000000000000000000000000000000000","message":"a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0"}},{"ConcealedNode":{"depth":3,"hash":"24de25d99e00e7d39e0f1cf05eee02c708004265e7f1fdc238a17b913af7e471"}},{"ConcealedNode":{"depth":2,"hash":"ec8f0b1576c5e9b1a91d51cbc6c0d40e20588b51eda0f88cbdc1bb31ed039909"}},{"ConcealedNode":{"depth":1,"hash":"1133c2e8545d1b3873b15b0d117c34860b23568dafa9891c51eb60f216dbf612"}}],"entropy":null}"#
+        );
+        let decoded = serde_json::from_str::<MerkleBlock>(&json).unwrap();
+        assert_eq!(decoded, block);
+        assert_eq!(decoded.commit_id(), tree.commit_id());
+
+        let full = MerkleBlock::from(&tree);
+        let decoded =
+            serde_json::from_str::<MerkleBlock>(&serde_json::to_string(&full).unwrap()).unwrap();
+        assert_eq!(decoded, full);
+        assert_eq!(decoded.commit_id(), tree.commit_id());
+
+        let proof = block.to_merkle_proof(ProtocolId::from([0; 32])).unwrap();
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(
+            json,
+            r#"{"pos":0,"cofactor":0,"path":["1133c2e8545d1b3873b15b0d117c34860b23568dafa9891c51eb60f216dbf612","ec8f0b1576c5e9b1a91d51cbc6c0d40e20588b51eda0f88cbdc1bb31ed039909","24de25d99e00e7d39e0f1cf05eee02c708004265e7f1fdc238a17b913af7e471"]}"#
+        );
+        assert_eq!(serde_json::from_str::<MerkleProof>(&json).unwrap(), proof);
+    }
+
+    /// Finds protocol id absent from the tree, which slot is either empty or
+    /// occupied by a commitment under some other protocol.
+    fn absent_protocol_id(tree: &MerkleTree, occupied: bool) -> ProtocolId {
+        (0u64..)
+            .map(|no| ProtocolId::from(u256::from(no).to_le_bytes()))
+            .filter(|protocol_id| !tree.messages.contains_key(protocol_id))
+            .find(|protocol_id| {
+                tree.map.contains_key(&tree.protocol_id_pos(*protocol_id)) == occupied
+            })
+            .expect("tree has both empty and occupied slots")
+    }
+
+    #[test]
+    fn non_inclusion_empty_slot() {
+        let msgs = make_random_messages(9);
+        let tree = make_random_tree(&msgs);
+        let block = MerkleBlock::from(&tree);
+
+        let protocol_id = absent_protocol_id(&tree, false);
+        let pos = block.protocol_id_pos(protocol_id);
+        let proof = block.non_inclusion_proof(protocol_id).unwrap();
+        assert_eq!(proof.leaf(), Leaf::entropy(tree.entropy, pos));
+        assert_eq!(proof.convolve(protocol_id), Ok(tree.commit_id()));
+        assert_eq!(proof.verify(protocol_id, tree.commit_id()), Ok(()));
+
+        let other = make_random_tree(&make_random_messages(9));
+        assert_eq!(
+            proof.verify(protocol_id, other.commit_id()),
+            Err(NonInclusionError::CommitmentMismatch)
+        );
+    }
+
+    #[test]
+    fn non_inclusion_other_protocol() {
+        let msgs = make_random_messages(9);
+        let tree = make_random_tree(&msgs);
+        let block = MerkleBlock::from(&tree);
+
+        let protocol_id = absent_protocol_id(&tree, true);
+        let pos = block.protocol_id_pos(protocol_id);
+        let (other_id, other_msg) = *tree.map.get(&pos).unwrap();
+        let proof = block.non_inclusion_proof(protocol_id).unwrap();
+        assert_eq!(proof.leaf(), Leaf::inhabited(other_id, other_msg));
+        assert_eq!(proof.convolve(protocol_id), Ok(tree.commit_id()));
+        assert_eq!(proof.verify(protocol_id, tree.commit_id()), Ok(()));
+    }
+
+    #[test]
+    fn non_inclusion_misplaced_leaf() {
+        let msgs = make_random_messages(9);
+        let tree = make_random_tree(&msgs);
+        let mut block = MerkleBlock::from(&tree);
+
+        let protocol_id = absent_protocol_id(&tree, false);
+        let pos = block.protocol_id_pos(protocol_id);
+        let index = (0..block.cross_section.len())
+            .find(|index| *index as u32 != pos && !block.cross_section[*index].is_leaf())
+            .unwrap();
+        block.cross_section[index] = TreeNode::CommitmentLeaf {
+            protocol_id,
+            message: default!(),
+        };
+        assert_eq!(
+            block.non_inclusion_proof(protocol_id),
+            Err(NonInclusionError::Included(protocol_id))
+        );
+    }
+
+    #[test]
+    fn non_inclusion_occupied() {
+        let msgs = make_random_messages(9);
+        let tree = make_random_tree(&msgs);
+        let block = MerkleBlock::from(&tree);
+
+        let (pid, msg) = msgs.first_key_value().unwrap();
+        assert_eq!(block.non_inclusion_proof(*pid), Err(NonInclusionError::Included(*pid)));
+
+        let proof = block.to_merkle_proof(*pid).unwrap();
+        let forged = NonInclusionProof {
+            leaf: Leaf::inhabited(*pid, *msg),
+            proof: proof.clone(),
+        };
+        assert_eq!(forged.verify(*pid, tree.commit_id()), Err(NonInclusionError::Included(*pid)));
+
+        let forged = NonInclusionProof {
+            leaf: Leaf::entropy(tree.entropy, proof.pos()),
+            proof,
+        };
+        assert_eq!(
+            forged.verify(*pid, tree.commit_id()),
+            Err(NonInclusionError::CommitmentMismatch)
+        );
+
+        let mut concealed = block.clone();
+        concealed.conceal_other(*pid).unwrap();
+        let (other, _) = msgs.last_key_value().unwrap();
+        assert_eq!(
+            concealed.non_inclusion_proof(*other),
+            Err(NonInclusionError::SlotConcealed(*other))
+        );
+    }
 }
@@ -30,8 +30,11 @@ mod block;
 pub use atoms::{
     Commitment, Leaf, Message, MessageMap, MultiSource, ProtocolId, MPC_MINIMAL_DEPTH,
 };
-pub use block::{InvalidProof, LeafNotKnown, MergeError, MerkleBlock, MerkleProof};
-pub use tree::{Error, MerkleTree};
+pub use block::{
+    InclusionError, InvalidProof, LeafNotKnown, MergeError, MerkleBlock, MerkleProof,
+    NonInclusionError, NonInclusionProof,
+};
+pub use tree::{Error, MerkleTree, SlotCache};
 
 /// Marker trait for variates of LNPBP-4 commitment proofs, which differ by the
 /// amount of concealed information.
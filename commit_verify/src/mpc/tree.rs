@@ -19,6 +19,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
 use amplify::confinement::{LargeVec, MediumOrdMap};
 use amplify::num::{u256, u5};
 use amplify::Wrapper;
@@ -107,6 +109,9 @@ mod commit {
         /// the provided number of messages ({0}) can't fit LNPBP-4 commitment
         /// size limits for a given set of protocol ids.
         CantFitInMaxSlots(usize),
+
+        /// more than one message is provided for the protocol {0}.
+        DuplicateProtocol(ProtocolId),
     }
 
     /// # Panics
@@ -179,7 +184,45 @@ pub(super) fn protocol_id_pos(protocol_id: ProtocolId, cofactor: u16, width: u32
     rem.low_u64() as u32
 }
 
+/// Cache memoizing positions of protocol ids within LNPBP-4 trees, for use
+/// when many trees or blocks are built or verified for the same set of
+/// protocols.
+///
+/// Positions are cached per protocol id, tree width and cofactor, since the
+/// position depends on all of them.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct SlotCache {
+    slots: BTreeMap<(ProtocolId, u32, u16), u32>,
+    misses: usize,
+}
+
+impl SlotCache {
+    /// Constructs empty cache.
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns position of `protocol_id` within the leaves of a tree with the
+    /// given `width` and `cofactor`, computing it only if it is not cached yet.
+    pub fn slot(&mut self, protocol_id: ProtocolId, width: u32, cofactor: u16) -> u32 {
+        *self
+            .slots
+            .entry((protocol_id, width, cofactor))
+            .or_insert_with(|| {
+                self.misses += 1;
+                protocol_id_pos(protocol_id, cofactor, width)
+            })
+    }
+
+    /// Number of positions which were actually computed by the cache.
+    pub fn misses(&self) -> usize { self.misses }
+}
+
 impl MerkleTree {
+    /// Computes position for a given `protocol_id` within the tree leaves
+    /// using a shared [`SlotCache`].
+    pub fn protocol_id_pos_cached(&self, protocol_id: ProtocolId, cache: &mut SlotCache) -> u32 {
+        cache.slot(protocol_id, self.width(), self.cofactor)
+    }
+
     /// Computes position for a given `protocol_id` within the tree leaves.
     pub fn protocol_id_pos(&self, protocol_id: ProtocolId) -> u32 {
         protocol_id_pos(protocol_id, self.cofactor, self.width())
@@ -236,7 +279,7 @@ pub(crate) mod test_helpers {
         let src = MultiSource {
             min_depth: u5::ZERO,
             messages: Confined::try_from_iter(msgs.iter().map(|(a, b)| (*a, *b))).unwrap(),
-            static_entropy: None,
+            static_entropy: Some(random()),
         };
         MerkleTree::try_commit(&src).unwrap()
     }
@@ -244,16 +287,122 @@ pub(crate) mod test_helpers {
 
 #[cfg(test)]
 mod test {
-    use std::collections::BTreeSet;
+    use std::collections::{BTreeMap, BTreeSet};
 
     use amplify::num::u5;
-    use amplify::Wrapper;
+    use amplify::{confinement, Bytes32, Wrapper};
     use rand::random;
     use strict_encoding::{StreamWriter, StrictEncode};
 
     use crate::mpc::tree::test_helpers::{make_random_messages, make_random_tree};
-    use crate::mpc::MerkleBlock;
-    use crate::{CommitId, Conceal};
+    use crate::mpc::tree::{protocol_id_pos, SlotCache};
+    use crate::mpc::{
+        Error, Leaf, MerkleBlock, MerkleTree, Message, MessageMap, MultiSource, ProtocolId,
+    };
+    use crate::{CommitId, Conceal, TryCommitVerify};
+
+    #[test]
+    fn slot_cache() {
+        let mut cache = SlotCache::new();
+        let ids = (0..16u8)
+            .map(|no| ProtocolId::from([no; 32]))
+            .collect::<Vec<_>>();
+        for _ in 0..3 {
+            for id in &ids {
+                for width in [8u32, 256, 1 << 16] {
+                    for cofactor in [0u16, 3] {
+                        assert_eq!(
+                            cache.slot(*id, width, cofactor),
+                            protocol_id_pos(*id, cofactor, width)
+                        );
+                    }
+                }
+            }
+        }
+        assert_eq!(cache.misses(), ids.len() * 3 * 2);
+
+        let msgs = make_random_messages(9);
+        let tree = make_random_tree(&msgs);
+        for id in msgs.keys() {
+            assert_eq!(tree.protocol_id_pos_cached(*id, &mut cache), tree.protocol_id_pos(*id));
+        }
+        let block = MerkleBlock::from(tree.clone());
+        let misses = cache.misses();
+        for id in msgs.keys() {
+            assert_eq!(block.protocol_id_pos_cached(*id, &mut cache), tree.protocol_id_pos(*id));
+        }
+        assert_eq!(cache.misses(), misses);
+    }
+
+    #[test]
+    fn source_messages() {
+        let msgs = make_random_messages(9);
+        let source = MultiSource::with_messages(msgs.iter().map(|(a, b)| (*a, *b))).unwrap();
+        assert_eq!(source.messages.as_inner(), &msgs);
+        assert_eq!(source.min_depth, MultiSource::default().min_depth);
+        let tree = MerkleTree::try_commit(&MultiSource {
+            static_entropy: Some(0),
+            ..source.clone()
+        })
+        .unwrap();
+        assert_eq!(tree.messages.as_inner(), &msgs);
+
+        let (protocol_id, message) = msgs.first_key_value().map(|(a, b)| (*a, *b)).unwrap();
+        let duplicated = msgs
+            .iter()
+            .map(|(a, b)| (*a, *b))
+            .chain([(protocol_id, Message::from_inner(Bytes32::with_fill(0xAA)))]);
+        assert_eq!(
+            MultiSource::with_messages(duplicated),
+            Err(Error::DuplicateProtocol(protocol_id))
+        );
+
+        let mut source = source;
+        assert_eq!(
+            source.insert_message(protocol_id, message),
+            Err(Error::DuplicateProtocol(protocol_id))
+        );
+        assert_eq!(source.messages.as_inner(), &msgs);
+    }
+
+    #[test]
+    fn source_commitments() {
+        let data = Leaf::entropy(0xbeefcafe, 1);
+        let protocol_id = ProtocolId::from([0x01; 32]);
+        let mut source = MultiSource::default();
+        source.insert_commitment(protocol_id, &data).unwrap();
+        assert_eq!(source.messages.get(&protocol_id), Some(&Message::commit_to(&data)));
+
+        let other = Leaf::entropy(0xbeefcafe, 2);
+        assert_eq!(
+            source.insert_commitment(protocol_id, &other),
+            Err(Error::DuplicateProtocol(protocol_id))
+        );
+        assert_eq!(source.messages.get(&protocol_id), Some(&Message::commit_to(&data)));
+    }
+
+    #[test]
+    fn source_capacity_huge() {
+        let id = |no: u32| {
+            let mut id = [0u8; 32];
+            id[..4].copy_from_slice(&no.to_be_bytes());
+            ProtocolId::from(id)
+        };
+        let capacity = confinement::U24 as u32;
+        // Keys are produced in order, allowing bulk construction of the map
+        let messages = (0..capacity)
+            .map(|no| (id(no), Message::from([0xA5; 32])))
+            .collect::<BTreeMap<_, _>>();
+        let mut source = MultiSource {
+            messages: MessageMap::try_from(messages).unwrap(),
+            ..default!()
+        };
+        assert_eq!(
+            source.insert_message(id(capacity), Message::from([0x5A; 32])),
+            Err(Error::TooManyMessages(capacity as usize + 1))
+        );
+        assert_eq!(source.messages.len(), capacity as usize);
+    }
 
     #[test]
     #[should_panic(expected = "Empty")]
@@ -0,0 +1,327 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Set commitments supporting reconciliation of the sets (invertible bloom
+//! lookup tables).
+
+use std::collections::BTreeSet;
+
+use amplify::confinement::MediumVec;
+use amplify::Bytes32;
+use strict_encoding::{DecodeError, ReadStruct, StrictDecode, StrictDumb, TypedRead};
+
+use crate::{DigestExt, Sha256, StrictHash, LIB_NAME_COMMIT_VERIFY};
+
+/// Number of cells each of the set elements is inserted into.
+const CELLS_PER_ELEMENT: usize = 3;
+
+/// Error reconciling sets from their commitments.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ReconcileError {
+    /// set commitments of a different size ({0} and {1} cells) can't be
+    /// subtracted.
+    SizeMismatch(usize, usize),
+
+    /// set difference exceeds the capacity of the commitment and can't be
+    /// recovered.
+    CapacityExceeded,
+}
+
+/// Symmetric difference of two sets recovered by
+/// [`ReconcilableSetCommitment::reconcile`].
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct SetDifference {
+    /// Elements present only in the first (minuend) set.
+    pub added: BTreeSet<Bytes32>,
+    /// Elements present only in the second (subtrahend) set.
+    pub removed: BTreeSet<Bytes32>,
+}
+
+/// Cell of [`ReconcilableSetCommitment`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+pub struct ReconcileCell {
+    /// Number of the elements inserted into the cell.
+    pub count: i32,
+    /// XOR of the elements inserted into the cell.
+    pub key_sum: Bytes32,
+    /// XOR of the tagged hashes of the elements inserted into the cell.
+    pub hash_sum: Bytes32,
+}
+
+impl ReconcileCell {
+    fn toggle(&mut self, element: &Bytes32, checksum: &Bytes32, count: i32) {
+        self.count = self.count.wrapping_add(count);
+        self.key_sum = xor(&self.key_sum, element);
+        self.hash_sum = xor(&self.hash_sum, checksum);
+    }
+
+    fn is_empty(&self) -> bool { *self == Self::default() }
+
+    fn pure_element(&self) -> Option<Bytes32> {
+        if (self.count == 1 || self.count == -1) &&
+            ReconcilableSetCommitment::checksum(&self.key_sum) == self.hash_sum
+        {
+            Some(self.key_sum)
+        } else {
+            None
+        }
+    }
+}
+
+/// Commitment to a set of 32-byte elements, such that subtracting commitments
+/// of two sets allows to recover their symmetric difference, as long as it
+/// doesn't exceed the capacity of the commitment.
+///
+/// The commitment is an invertible bloom lookup table (IBLT), where each of the
+/// elements is added to three cells (one per each third of the table), selected
+/// with a tagged hash of the element. The recovery is probabilistic: it
+/// succeeds with high probability when the difference fits the capacity, and
+/// reports [`ReconcileError::CapacityExceeded`] otherwise.
+///
+/// The commitment id is a [`StrictHash`] of the table.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictEncode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[derive(CommitEncode)]
+#[commit_encode(crate = crate, strategy = strict, id = StrictHash)]
+pub struct ReconcilableSetCommitment {
+    cells: MediumVec<ReconcileCell>,
+}
+
+impl StrictDumb for ReconcilableSetCommitment {
+    fn strict_dumb() -> Self { Self::with_capacity(1) }
+}
+
+impl StrictDecode for ReconcilableSetCommitment {
+    fn strict_decode(reader: &mut impl TypedRead) -> Result<Self, DecodeError> {
+        reader.read_struct(|r| {
+            let cells: MediumVec<ReconcileCell> = r.read_field(fname!("cells"))?;
+            if cells.is_empty() || cells.len() % CELLS_PER_ELEMENT != 0 {
+                return Err(DecodeError::DataIntegrityError(format!(
+                    "reconcilable set commitment must have a non-zero number of cells divisible \
+                     by {CELLS_PER_ELEMENT}, while {} cells are present",
+                    cells.len()
+                )));
+            }
+            Ok(Self { cells })
+        })
+    }
+}
+
+impl ReconcilableSetCommitment {
+    /// Parent tag for the hashes mapping elements to their cells and
+    /// checksums, which are tagged with `index` and `checksum` sub-tags.
+    pub const TAG: &'static str = "urn:ubideco:reconcile:iblt#2024-10-14";
+
+    /// Constructs commitment to an empty set, which allows recovery of a set
+    /// difference of up to `capacity` elements.
+    pub fn with_capacity(capacity: u16) -> Self {
+        let width = capacity as usize + 2;
+        Self {
+            cells: MediumVec::try_from(vec![ReconcileCell::default(); width * CELLS_PER_ELEMENT])
+                .expect("u16 capacity fits the cell limit"),
+        }
+    }
+
+    /// Constructs commitment to a set of `elements`, which allows recovery of
+    /// a set difference of up to `capacity` elements.
+    pub fn with_elements(capacity: u16, elements: impl IntoIterator<Item = Bytes32>) -> Self {
+        let mut commitment = Self::with_capacity(capacity);
+        for element in elements {
+            commitment.insert(element);
+        }
+        commitment
+    }
+
+    /// Returns the number of the cells in the commitment.
+    pub fn len(&self) -> usize { self.cells.len() }
+
+    /// Detects whether the commitment doesn't contain any elements.
+    pub fn is_empty(&self) -> bool { self.cells.iter().all(ReconcileCell::is_empty) }
+
+    /// Adds `element` to the committed set.
+    ///
+    /// The set must not contain the element already.
+    pub fn insert(&mut self, element: Bytes32) { self.toggle(&element, 1) }
+
+    /// Removes `element` from the committed set.
+    ///
+    /// The set must contain the element.
+    pub fn remove(&mut self, element: Bytes32) { self.toggle(&element, -1) }
+
+    /// Subtracts commitment to the `other` set from the commitment to this
+    /// set, producing commitment to the symmetric difference of the sets.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ReconcileError::SizeMismatch`] if the commitments are of
+    /// a different size.
+    pub fn subtract(&self, other: &Self) -> Result<Self, ReconcileError> {
+        if self.len() != other.len() {
+            return Err(ReconcileError::SizeMismatch(self.len(), other.len()));
+        }
+        let mut diff = self.clone();
+        for (cell, other) in diff.cells.iter_mut().zip(&other.cells) {
+            cell.count = cell.count.wrapping_sub(other.count);
+            cell.key_sum = xor(&cell.key_sum, &other.key_sum);
+            cell.hash_sum = xor(&cell.hash_sum, &other.hash_sum);
+        }
+        Ok(diff)
+    }
+
+    /// Recovers symmetric difference of this set and the `other` set from
+    /// their commitments.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ReconcileError::SizeMismatch`] if the commitments are of
+    /// a different size, and with [`ReconcileError::CapacityExceeded`] if the
+    /// difference can't be recovered.
+    pub fn reconcile(&self, other: &Self) -> Result<SetDifference, ReconcileError> {
+        let mut diff = self.subtract(other)?;
+        let mut result = SetDifference::default();
+        // Each of the recovered elements empties at least one cell, so with
+        // a valid table the peeling can't take more steps than there are
+        // cells; the limit protects from looping on malformed tables.
+        for _ in 0..diff.len() {
+            let Some((element, count)) = diff
+                .cells
+                .iter()
+                .find_map(|cell| cell.pure_element().map(|element| (element, cell.count)))
+            else {
+                break;
+            };
+            diff.toggle(&element, -count);
+            if count > 0 {
+                result.added.insert(element);
+            } else {
+                result.removed.insert(element);
+            }
+        }
+        if !diff.is_empty() {
+            return Err(ReconcileError::CapacityExceeded);
+        }
+        Ok(result)
+    }
+
+    fn toggle(&mut self, element: &Bytes32, count: i32) {
+        let checksum = Self::checksum(element);
+        let width = self.len() / CELLS_PER_ELEMENT;
+        let mut engine =
+            Sha256::from_subtag(Self::TAG, "index").expect("sub-tag name has no separator");
+        engine.input_raw(element.as_slice());
+        let index = engine.finish();
+        for (no, bytes) in index.chunks_exact(4).take(CELLS_PER_ELEMENT).enumerate() {
+            let pos = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+            self.cells[no * width + pos % width].toggle(element, &checksum, count);
+        }
+    }
+
+    fn checksum(element: &Bytes32) -> Bytes32 {
+        let mut engine =
+            Sha256::from_subtag(Self::TAG, "checksum").expect("sub-tag name has no separator");
+        engine.input_raw(element.as_slice());
+        engine.finish().into()
+    }
+}
+
+fn xor(a: &Bytes32, b: &Bytes32) -> Bytes32 {
+    let mut res = [0u8; 32];
+    for (res, (a, b)) in res.iter_mut().zip(a.iter().zip(b.iter())) {
+        *res = a ^ b;
+    }
+    res.into()
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+    use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+    use super::*;
+    use crate::CommitId;
+
+    impl StrictSerialize for ReconcilableSetCommitment {}
+    impl StrictDeserialize for ReconcilableSetCommitment {}
+
+    fn element(no: u16) -> Bytes32 {
+        let mut engine = Sha256::from_tag("test");
+        engine.input_raw(&no.to_le_bytes());
+        engine.finish().into()
+    }
+
+    #[test]
+    fn reconcile() {
+        let alice = ReconcilableSetCommitment::with_elements(8, (0..20).map(element));
+        let bob = ReconcilableSetCommitment::with_elements(8, (3..24).map(element));
+        assert_ne!(alice.commit_id(), bob.commit_id());
+
+        let diff = alice.reconcile(&bob).unwrap();
+        assert_eq!(diff.added, (0..3).map(element).collect());
+        assert_eq!(diff.removed, (20..24).map(element).collect());
+        let diff = bob.reconcile(&alice).unwrap();
+        assert_eq!(diff.added, (20..24).map(element).collect());
+        assert_eq!(diff.removed, (0..3).map(element).collect());
+
+        assert_eq!(alice.reconcile(&alice), Ok(SetDifference::default()));
+        assert!(alice.subtract(&alice).unwrap().is_empty());
+
+        let mut carol = alice.clone();
+        carol.remove(element(0));
+        carol.insert(element(200));
+        assert_ne!(carol, alice);
+        carol.remove(element(200));
+        carol.insert(element(0));
+        assert_eq!(carol.commit_id(), alice.commit_id());
+    }
+
+    #[test]
+    fn reconcile_errors() {
+        let alice = ReconcilableSetCommitment::with_elements(2, (0..20).map(element));
+        let bob = ReconcilableSetCommitment::with_elements(2, (10..30).map(element));
+        assert_eq!(alice.reconcile(&bob), Err(ReconcileError::CapacityExceeded));
+
+        let other = ReconcilableSetCommitment::with_capacity(3);
+        assert_eq!(alice.reconcile(&other), Err(ReconcileError::SizeMismatch(12, 15)));
+    }
+
+    #[test]
+    fn strict_encoding() {
+        let set = ReconcilableSetCommitment::with_elements(4, (0..10).map(element));
+        let data = set.to_strict_serialized::<{ u16::MAX as usize }>().unwrap();
+        let decoded =
+            ReconcilableSetCommitment::from_strict_serialized::<{ u16::MAX as usize }>(data)
+                .unwrap();
+        assert_eq!(decoded, set);
+
+        let invalid = [2u8, 0, 0]
+            .into_iter()
+            .chain([0u8; 68 * 2])
+            .collect::<Vec<_>>();
+        assert!(ReconcilableSetCommitment::from_strict_serialized::<{ u16::MAX as usize }>(
+            Confined::try_from(invalid).unwrap()
+        )
+        .is_err());
+    }
+}
@@ -21,11 +21,15 @@
 
 use strict_types::{CompileError, LibBuilder, TypeLib};
 
-use crate::{mpc, MerkleHash, MerkleNode, ReservedBytes, StrictHash, LIB_NAME_COMMIT_VERIFY};
+use crate::{
+    mpc, MerkleHash, MerkleNode, MerkleShape, MerkleShapeHash, ReservedBytes, StrictHash,
+    LIB_NAME_COMMIT_VERIFY,
+};
 
 pub const LIB_ID_COMMIT_VERIFY: &str =
-    "stl:4dHdHDpB-17hoScv-Z8f00we-UaQ4D!G-tTV1vSN-JseSamU#tennis-peace-olympic";
+    "stl:qw9y2MH!-pCStIiB-x5!VhGC-fQfGeiR-NYqiNnC-hA4I4XY#voice-forward-slang";
 
+#[allow(clippy::result_large_err)]
 fn _commit_verify_stl() -> Result<TypeLib, CompileError> {
     LibBuilder::new(libname!(LIB_NAME_COMMIT_VERIFY), tiny_bset! {
         strict_types::stl::std_stl().to_dependency()
@@ -36,10 +40,13 @@ fn _commit_verify_stl() -> Result<TypeLib, CompileError> {
     .transpile::<mpc::MerkleTree>()
     .transpile::<mpc::MerkleBlock>()
     .transpile::<mpc::MerkleProof>()
+    .transpile::<mpc::NonInclusionProof>()
     .transpile::<mpc::Leaf>()
     .transpile::<mpc::Commitment>()
     .transpile::<MerkleNode>()
     .transpile::<MerkleHash>()
+    .transpile::<MerkleShape>()
+    .transpile::<MerkleShapeHash>()
     .transpile::<StrictHash>()
     .compile()
 }
@@ -0,0 +1,157 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pinned test vectors for commitment constructions, protecting them from
+//! accidental changes during refactoring.
+
+use amplify::confinement::TinyVec;
+use amplify::hex::{FromHex, ToHex};
+
+use crate::mpc::Leaf;
+use crate::{mpc, CommitmentId, DigestExt, MerkleHash, Sha256, StrictHash};
+
+/// Test vector for a commitment construction.
+struct ProtocolVector {
+    tag: &'static str,
+    /// Hex-encoded input data.
+    input: &'static str,
+    /// Hex-encoded expected commitment.
+    expected: &'static str,
+}
+
+/// Commitment construction producing 32-byte commitment from a tag and input
+/// data.
+type Construction = fn(tag: &'static str, input: &[u8]) -> [u8; 32];
+
+/// Tagged SHA256 hash of the input data.
+fn tagged_hash(tag: &'static str, input: &[u8]) -> [u8; 32] {
+    let mut engine = Sha256::from_tag(tag);
+    engine.input_raw(input);
+    engine.finish()
+}
+
+/// Merkle root of entropy leaves, where input consists of little-endian
+/// 64-bit entropy values for each of the leaves.
+fn merklize(tag: &'static str, input: &[u8]) -> [u8; 32] {
+    assert_eq!(tag, MerkleHash::TAG, "merklization uses merkle node tag");
+    let leaves = input
+        .chunks(8)
+        .enumerate()
+        .map(|(pos, chunk)| {
+            let entropy = u64::from_le_bytes(chunk.try_into().expect("8-byte entropy"));
+            Leaf::entropy(entropy, pos as u32)
+        })
+        .collect::<Vec<_>>();
+    let leaves = TinyVec::try_from(leaves).expect("too many leaves");
+    MerkleHash::merklize(&leaves).to_byte_array()
+}
+
+const TAGGED_HASH_VECTORS: &[ProtocolVector] = &[
+    ProtocolVector {
+        tag: "",
+        input: "",
+        expected: "2dba5dbc339e7316aea2683faf839c1b7b1ee2313db792112588118df066aa35",
+    },
+    ProtocolVector {
+        tag: StrictHash::TAG,
+        input: "",
+        expected: "46f8cc75e5553a352f50fe20c4cb93374ec4b119c3c11598a0f4835413aae072",
+    },
+    ProtocolVector {
+        tag: StrictHash::TAG,
+        input: "deadbeef",
+        expected: "1b78c2099f07020b1ec2f8b5ecde833fc010a52ce346690b33cafa406f559c5c",
+    },
+    ProtocolVector {
+        tag: MerkleHash::TAG,
+        input: "00",
+        expected: "e7345b8b8ec09eab55b2784e8e5f5642c3ec6c1fd5e9fe8755ecc28cb620670e",
+    },
+    ProtocolVector {
+        tag: MerkleHash::TAG,
+        input: "deadbeef",
+        expected: "04002f48ea1c4c9ce6615e114e398c857a1a4e8f7b43c931940870c61144f33e",
+    },
+    ProtocolVector {
+        tag: mpc::Commitment::TAG,
+        input: "",
+        expected: "c8836cf4d4a0d58200b86a7ab287e1a18cf25fb44d7a09f1d7ef21cb68d75178",
+    },
+    ProtocolVector {
+        tag: mpc::Commitment::TAG,
+        input: "00",
+        expected: "1d52029ac616af8f51bbf977b02d6c8b2eadd395bb900fa666cd4de81b0fa4da",
+    },
+];
+
+const MERKLIZE_VECTORS: &[ProtocolVector] = &[
+    ProtocolVector {
+        tag: MerkleHash::TAG,
+        input: "",
+        expected: "5442b1a1ae7badee5391857589104a594e466ffc6fd6484d2890b466537f7f7a",
+    },
+    ProtocolVector {
+        tag: MerkleHash::TAG,
+        input: "0100000000000000",
+        expected: "613a267d4b442ce1d1dd58da5f455660511735efc105fa3094a794dc595dbe07",
+    },
+    ProtocolVector {
+        tag: MerkleHash::TAG,
+        input: "01000000000000000201010100000000",
+        expected: "b9a3b4e8a5e1b43fb2f6959f721fed512736fd21fd0a1140118d1ea7ae7cbe97",
+    },
+    ProtocolVector {
+        tag: MerkleHash::TAG,
+        input: "010000000000000002010101000000000302020200000000",
+        expected: "b1c23f75988f91eb10f5ed5abd929c9bbb5f0d4474d6561e1be4688fbf59821c",
+    },
+    ProtocolVector {
+        tag: MerkleHash::TAG,
+        input: "01000000000000000201010100000000030202020000000004030303000000000504040400000000",
+        expected: "542f03e7b85f23afb73e4234334bc5a71f56adba3f98c966ccc437b54c76a88c",
+    },
+    ProtocolVector {
+        tag: MerkleHash::TAG,
+        input: "01000000000000000201010100000000030202020000000004030303000000000504040400000000060505050000000007060606000000000807070700000000",
+        expected: "f55cb26218d3edbc6faaaf69de9e637c1e590d0eb7e6614ce967a61c1f751c00",
+    },
+];
+
+const REGISTRY: &[(&str, Construction, &[ProtocolVector])] =
+    &[("tagged hash", tagged_hash, TAGGED_HASH_VECTORS), ("merklize", merklize, MERKLIZE_VECTORS)];
+
+#[test]
+fn protocol_vectors() {
+    for (name, construction, vectors) in REGISTRY {
+        assert!(!vectors.is_empty(), "no test vectors for {name}");
+        for vector in *vectors {
+            let input = Vec::<u8>::from_hex(vector.input).expect("invalid input hex");
+            let commitment = construction(vector.tag, &input);
+            assert_eq!(
+                commitment.to_hex(),
+                vector.expected,
+                "{name} vector for tag `{}` and input `{}`",
+                vector.tag,
+                vector.input
+            );
+        }
+    }
+}
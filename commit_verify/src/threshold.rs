@@ -0,0 +1,269 @@
+// Client-side-validation foundation libraries.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Threshold (k-of-n) commitments aggregating shares of multiple participants.
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+
+use amplify::confinement::{NonEmptyVec, U16 as U16MAX, U8};
+use strict_encoding::StrictDumb;
+
+use crate::{
+    CommitEncode, CommitId, CommitmentId, DigestExt, Sha256, StrictHash, LIB_NAME_COMMIT_VERIFY,
+};
+
+/// Errors creating or verifying [`ThresholdCommitment`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ThresholdError {
+    /// threshold {threshold} is invalid for {participants} participants.
+    InvalidThreshold { threshold: u16, participants: usize },
+
+    /// share of participant {0} is not known to the commitment.
+    UnknownParticipant(u16),
+
+    /// share of participant {0} doesn't match the commitment.
+    InvalidShare(u16),
+
+    /// share of participant {0} is provided more than once.
+    DuplicateShare(u16),
+
+    /// only {valid} shares are provided, while the threshold is {threshold}.
+    BelowThreshold { valid: usize, threshold: u16 },
+}
+
+/// Creates commitment to the `share` of a participant with the given `index`,
+/// binding the share to the participant.
+///
+/// The commitment is a [`StrictHash`] tagged with `threshold-share` sub-tag of
+/// the [`StrictHash::TAG`] (see [`crate::subtag`]), computed over a 16-bit
+/// little-endian participant index followed by the length-prefixed
+/// [`CommitId::commit_id`] of the share.
+pub fn share_commitment<T>(index: u16, share: &T) -> StrictHash
+where
+    T: CommitEncode,
+    T::CommitmentId: Borrow<[u8]>,
+{
+    let mut engine = Sha256::from_subtag(StrictHash::TAG, "threshold-share")
+        .expect("sub-tag name has no separator");
+    engine.input_raw(&index.to_le_bytes());
+    engine.input_with_len::<U8>(share.commit_id().borrow());
+    engine.into()
+}
+
+/// Commitment aggregating shares of `n` participants, which verifies when
+/// shares of at least `threshold` participants are presented.
+///
+/// The commitment keeps [`share_commitment`] of each of the participants,
+/// indexed by their position; its id is a [`StrictHash`] over the threshold
+/// and all of the share commitments.
+#[derive(Getters, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictEncode)]
+#[strict_type(lib = LIB_NAME_COMMIT_VERIFY)]
+#[derive(CommitEncode)]
+#[commit_encode(crate = crate, strategy = strict, id = StrictHash)]
+pub struct ThresholdCommitment {
+    /// Minimal number of participant shares required for the verification.
+    #[getter(as_copy)]
+    threshold: u16,
+
+    /// Commitments to the shares of each of the participants.
+    #[getter(skip)]
+    shares: NonEmptyVec<StrictHash, U16MAX>,
+}
+
+impl StrictDumb for ThresholdCommitment {
+    fn strict_dumb() -> Self {
+        Self {
+            threshold: 1,
+            shares: NonEmptyVec::with(StrictHash::strict_dumb()),
+        }
+    }
+}
+
+impl ThresholdCommitment {
+    /// Constructs commitment to the `shares` of the participants, where the
+    /// index of each participant is its position in the slice.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ThresholdError::InvalidThreshold`] if the threshold is
+    /// zero or exceeds the number of the participants, or if the number of
+    /// the participants exceeds `u16::MAX`.
+    pub fn new<T>(threshold: u16, shares: &[T]) -> Result<Self, ThresholdError>
+    where
+        T: CommitEncode,
+        T::CommitmentId: Borrow<[u8]>,
+    {
+        let invalid = ThresholdError::InvalidThreshold {
+            threshold,
+            participants: shares.len(),
+        };
+        if shares.len() > u16::MAX as usize {
+            return Err(invalid);
+        }
+        Self::with_share_commitments(
+            threshold,
+            shares
+                .iter()
+                .enumerate()
+                .map(|(index, share)| share_commitment(index as u16, share)),
+        )
+    }
+
+    /// Constructs commitment out of already-computed [`share_commitment`]s
+    /// of the participants, in the order of their indexes.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ThresholdError::InvalidThreshold`] if the threshold is
+    /// zero or exceeds the number of the participants, or if the number of
+    /// the participants exceeds `u16::MAX`.
+    pub fn with_share_commitments(
+        threshold: u16,
+        shares: impl IntoIterator<Item = StrictHash>,
+    ) -> Result<Self, ThresholdError> {
+        let shares = shares.into_iter().collect::<Vec<_>>();
+        let participants = shares.len();
+        let invalid = ThresholdError::InvalidThreshold {
+            threshold,
+            participants,
+        };
+        if threshold == 0 || threshold as usize > participants {
+            return Err(invalid);
+        }
+        let shares = NonEmptyVec::try_from(shares).map_err(|_| invalid)?;
+        Ok(Self { threshold, shares })
+    }
+
+    /// Returns the number of the participants.
+    pub fn participants(&self) -> usize { self.shares.len() }
+
+    /// Verifies that the provided `shares`, indexed by participants, match
+    /// the commitment and their number reaches the threshold.
+    ///
+    /// # Errors
+    ///
+    /// Errors if some of the shares belong to an unknown participant, don't
+    /// match the commitment or are duplicated, or if the number of the shares
+    /// is below the threshold (see [`ThresholdError`] variants).
+    pub fn verify<T>(&self, shares: &[(u16, T)]) -> Result<(), ThresholdError>
+    where
+        T: CommitEncode,
+        T::CommitmentId: Borrow<[u8]>,
+    {
+        let mut indexes = BTreeSet::new();
+        for (index, share) in shares {
+            let expected = self
+                .shares
+                .get(*index as usize)
+                .ok_or(ThresholdError::UnknownParticipant(*index))?;
+            if share_commitment(*index, share) != *expected {
+                return Err(ThresholdError::InvalidShare(*index));
+            }
+            if !indexes.insert(*index) {
+                return Err(ThresholdError::DuplicateShare(*index));
+            }
+        }
+        if indexes.len() < self.threshold as usize {
+            return Err(ThresholdError::BelowThreshold {
+                valid: indexes.len(),
+                threshold: self.threshold,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mpc::Leaf;
+
+    #[test]
+    fn threshold_commitment() {
+        let shares = [Leaf::entropy(1, 0), Leaf::entropy(2, 1), Leaf::entropy(3, 2)];
+        let cmt = ThresholdCommitment::new(2, &shares).unwrap();
+        assert_eq!(cmt.threshold(), 2);
+        assert_eq!(cmt.participants(), 3);
+
+        for (a, b) in [(0, 1), (0, 2), (1, 2), (2, 0)] {
+            let provided = [(a, shares[a as usize]), (b, shares[b as usize])];
+            assert_eq!(cmt.verify(&provided), Ok(()));
+        }
+        assert_eq!(cmt.verify(&[(0, shares[0]), (1, shares[1]), (2, shares[2])]), Ok(()));
+
+        for index in 0..3u16 {
+            assert_eq!(
+                cmt.verify(&[(index, shares[index as usize])]),
+                Err(ThresholdError::BelowThreshold {
+                    valid: 1,
+                    threshold: 2
+                })
+            );
+        }
+        assert_eq!(
+            cmt.verify(&[(0, shares[0]), (0, shares[0])]),
+            Err(ThresholdError::DuplicateShare(0))
+        );
+        // Valid share presented under the index of other participant
+        assert_eq!(
+            cmt.verify(&[(0, shares[1]), (1, shares[1])]),
+            Err(ThresholdError::InvalidShare(0))
+        );
+        assert_eq!(
+            cmt.verify(&[(0, shares[0]), (3, shares[1])]),
+            Err(ThresholdError::UnknownParticipant(3))
+        );
+    }
+
+    #[test]
+    fn threshold_commitment_id() {
+        let shares = [Leaf::entropy(1, 0), Leaf::entropy(2, 1), Leaf::entropy(3, 2)];
+        let cmt = ThresholdCommitment::new(2, &shares).unwrap();
+        assert_ne!(cmt.commit_id(), ThresholdCommitment::new(3, &shares).unwrap().commit_id());
+        let reordered = [shares[1], shares[0], shares[2]];
+        assert_ne!(cmt.commit_id(), ThresholdCommitment::new(2, &reordered).unwrap().commit_id());
+
+        assert_eq!(
+            ThresholdCommitment::new(0, &shares),
+            Err(ThresholdError::InvalidThreshold {
+                threshold: 0,
+                participants: 3
+            })
+        );
+        assert_eq!(
+            ThresholdCommitment::new(4, &shares),
+            Err(ThresholdError::InvalidThreshold {
+                threshold: 4,
+                participants: 3
+            })
+        );
+        assert_eq!(
+            ThresholdCommitment::new::<Leaf>(1, &[]),
+            Err(ThresholdError::InvalidThreshold {
+                threshold: 1,
+                participants: 0
+            })
+        );
+    }
+}
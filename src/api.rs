@@ -294,6 +294,7 @@ where Self::ValidationItem: 'client_data
     ///   [`ClientData::validate_internal_consistency`] method,
     /// - validates single-use-seal for the item using the provided `resolver`
     ///   object,
+    ///
     /// adding reported issues to the [`Status`] log returned by the function.
     ///
     /// The function should not fail on any validation failures and run the
@@ -404,8 +405,7 @@ mod test {
     #[allow(dead_code)]
     fn test() {
         #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
-        #[derive(Serialize, Deserialize)]
-        #[serde(crate = "serde_crate")]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
         struct Seal {}
 
         struct Protocol {}
@@ -435,8 +435,7 @@ mod test {
         struct Report {}
 
         #[derive(Clone, PartialEq, Eq, Hash, Debug)]
-        #[derive(Serialize, Deserialize)]
-        #[serde(crate = "serde_crate")]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
         struct Issue {
             seal: Seal,
         }